@@ -57,14 +57,14 @@ fn loss(x_data: &Array<f64, Ix2>, y_labels: &Array<f64, Ix1>, model: &MLP) -> (V
         ndarray::Zip::from(y_labels).and(&scores).map_collect(|&yi, scorei| (yi > 0.) == (scorei.get_data() > 0.));
     let accuracy = accuracy.fold(0., |acc, &hit| acc + if hit { 1. } else { 0. }) / accuracy.len() as f64;
 
-    return (total_loss, accuracy);
+    (total_loss, accuracy)
 }
 
 fn run_optimization(x_data: &Array<f64, Ix2>, y_labels: &Array<f64, Ix1>, model: &mut MLP, n_opt_steps: usize) {
     // optimization
     for k in 0..n_opt_steps {
         // forward
-        let (total_loss, accuracy) = loss(&x_data, &y_labels, &model);
+        let (total_loss, accuracy) = loss(x_data, y_labels, model);
 
         // backward
         model.zero_grad();
@@ -129,8 +129,9 @@ fn visualize_results(
             let point_x = coord_1.0 + step_x / 2.;
             let point_y = coord_1.1 + step_y / 2.;
 
-            let prediction =
-                model.call(&[mikrograd::new_value(point_x), mikrograd::new_value(point_y)]).first().unwrap().get_data();
+            let prediction = mikrograd::no_grad(|| {
+                model.call(&[mikrograd::new_value(point_x), mikrograd::new_value(point_y)]).first().unwrap().get_data()
+            });
             matrix[x].push(MatrixPoint { coords: [coord_1, coord_2], prediction });
         }
     }
@@ -143,7 +144,7 @@ fn visualize_results(
     chart
         .draw_series(x_data.map_axis(Axis(1), |data| {
             // TODO use different colors depending on labels
-            TriangleMarker::new((data[0], data[1]), 5, &YELLOW)
+            TriangleMarker::new((data[0], data[1]), 5, YELLOW)
         }))
         .unwrap();
 
@@ -0,0 +1,98 @@
+#[cfg(test)]
+#[path = "../tests/unit/compiled_test.rs"]
+mod compiled_test;
+
+use super::shared::{ForwardFn, GradientFn};
+use super::value::{GradientData, SharedGradientData};
+use super::{Float, Op, Value};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// A graph lowered into a flat sequence of scalar instructions over registers: it can be evaluated and
+/// differentiated on new inputs many times without building the graph again.
+/// NOTE hooks registered on nodes of the original graph are not applied.
+pub struct CompiledGraph {
+    registers: Vec<SharedGradientData>,
+    instructions: Vec<Instruction>,
+    inputs: Vec<Option<usize>>,
+}
+
+/// Computes data of the output register from data of argument registers.
+struct Instruction {
+    op: Op,
+    args: Vec<usize>,
+    out: usize,
+    forward_fn: ForwardFn,
+    gradient_fn: GradientFn,
+}
+
+impl CompiledGraph {
+    pub(crate) fn new(root: &Value, inputs: &[Value]) -> Self {
+        let mut indices = HashMap::new();
+        let mut registers = Vec::new();
+        let mut instructions = Vec::new();
+
+        root.topological_order().into_iter().for_each(|v| {
+            let out = registers.len();
+            registers.push(GradientData::new_shared(v.get_data()));
+            indices.insert(v.id(), out);
+
+            if let (Some(forward_fn), Some(gradient_fn)) = (v.forward_fn.clone(), v.gradient_fn.clone()) {
+                let args = v.children.iter().map(|child| indices[&child.id()]).collect();
+                instructions.push(Instruction { op: v.op().clone(), args, out, forward_fn, gradient_fn });
+            }
+        });
+
+        let inputs = inputs.iter().map(|input| indices.get(&input.id()).copied()).collect();
+
+        Self { registers, instructions, inputs }
+    }
+
+    /// Evaluates the graph using given data of inputs and returns data of the root.
+    pub fn eval(&mut self, inputs: &[Float]) -> Float {
+        assert_eq!(inputs.len(), self.inputs.len(), "eval requires data per input");
+
+        self.inputs.iter().zip(inputs).filter_map(|(idx, data)| idx.zip(Some(data))).for_each(|(idx, &data)| {
+            self.registers[idx].borrow_mut().data = data;
+        });
+
+        let mut args = Vec::new();
+        self.instructions.iter().for_each(|instruction| {
+            args.clear();
+            args.extend(instruction.args.iter().map(|&idx| self.registers[idx].borrow().data));
+            let data = (instruction.forward_fn)(args.as_slice());
+            self.registers[instruction.out].borrow_mut().data = data;
+        });
+
+        self.registers.last().map_or(0., |root| root.borrow().data)
+    }
+
+    /// Evaluates the graph using given data of inputs and returns gradients of the root with respect to
+    /// the inputs. Inputs which are not part of the graph get zero gradient.
+    pub fn grad(&mut self, inputs: &[Float]) -> Vec<Float> {
+        self.eval(inputs);
+
+        self.registers.iter().for_each(|register| register.borrow_mut().grad = 0.);
+        if let Some(root) = self.registers.last() {
+            root.borrow_mut().grad = 1.;
+        }
+
+        let mut args = Vec::new();
+        self.instructions.iter().rev().for_each(|instruction| {
+            args.clear();
+            args.extend(instruction.args.iter().map(|&idx| self.registers[idx].clone()));
+            (instruction.gradient_fn)(args.as_slice(), &self.registers[instruction.out]);
+        });
+
+        self.inputs.iter().map(|idx| idx.map_or(0., |idx| self.registers[idx].borrow().grad)).collect()
+    }
+}
+
+impl Display for CompiledGraph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.instructions.iter().try_for_each(|instruction| {
+            let args = instruction.args.iter().map(|idx| format!("%{idx}")).collect::<Vec<_>>();
+            writeln!(f, "%{} = {}({})", instruction.out, instruction.op, args.join(", "))
+        })
+    }
+}
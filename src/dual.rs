@@ -0,0 +1,160 @@
+#[cfg(test)]
+#[path = "../tests/unit/dual_test.rs"]
+mod dual_test;
+
+use super::Float;
+use auto_ops::{impl_op, impl_op_commutative};
+use std::fmt::{Display, Formatter};
+use std::iter::Sum;
+
+/// A dual number used for forward-mode automatic differentiation: it propagates a tangent
+/// (directional derivative) alongside the data during computation, so no graph is built.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DualValue {
+    data: Float,
+    tangent: Float,
+}
+
+impl DualValue {
+    /// Creates a new dual value with given data and tangent.
+    pub fn new(data: Float, tangent: Float) -> Self {
+        Self { data, tangent }
+    }
+
+    /// Creates a variable to differentiate with respect to: its tangent is one.
+    pub fn variable(data: Float) -> Self {
+        Self::new(data, 1.)
+    }
+
+    /// Creates a constant: its tangent is zero.
+    pub fn constant(data: Float) -> Self {
+        Self::new(data, 0.)
+    }
+
+    /// Returns underlying data.
+    pub fn get_data(&self) -> Float {
+        self.data
+    }
+
+    /// Returns a tangent (derivative in direction of the input tangents).
+    pub fn get_tangent(&self) -> Float {
+        self.tangent
+    }
+
+    fn chain(&self, data: Float, derivative: Float) -> Self {
+        Self::new(data, derivative * self.tangent)
+    }
+
+    pub fn pow(&self, rhs: Float) -> Self {
+        self.chain(self.data.powf(rhs), rhs * self.data.powf(rhs - 1.))
+    }
+
+    pub fn powi(&self, rhs: i32) -> Self {
+        self.chain(self.data.powi(rhs), rhs as Float * self.data.powi(rhs - 1))
+    }
+
+    pub fn relu(&self) -> Self {
+        self.chain(self.data.max(0.), if self.data > 0. { 1. } else { 0. })
+    }
+
+    pub fn tanh(&self) -> Self {
+        let data = self.data.tanh();
+        self.chain(data, 1. - data * data)
+    }
+
+    pub fn sigmoid(&self) -> Self {
+        let data = 1. / (1. + (-self.data).exp());
+        self.chain(data, data * (1. - data))
+    }
+
+    pub fn exp(&self) -> Self {
+        let data = self.data.exp();
+        self.chain(data, data)
+    }
+
+    pub fn ln(&self) -> Self {
+        self.chain(self.data.ln(), 1. / self.data)
+    }
+
+    pub fn sqrt(&self) -> Self {
+        let data = self.data.sqrt();
+        self.chain(data, 0.5 / data)
+    }
+
+    pub fn abs(&self) -> Self {
+        let sign = if self.data > 0. {
+            1.
+        } else if self.data < 0. {
+            -1.
+        } else {
+            0.
+        };
+        self.chain(self.data.abs(), sign)
+    }
+
+    pub fn sin(&self) -> Self {
+        self.chain(self.data.sin(), self.data.cos())
+    }
+
+    pub fn cos(&self) -> Self {
+        self.chain(self.data.cos(), -self.data.sin())
+    }
+
+    pub fn tan(&self) -> Self {
+        self.chain(self.data.tan(), 1. / self.data.cos().powi(2))
+    }
+
+    pub fn atan(&self) -> Self {
+        self.chain(self.data.atan(), 1. / (1. + self.data * self.data))
+    }
+
+    pub fn sinh(&self) -> Self {
+        self.chain(self.data.sinh(), self.data.cosh())
+    }
+
+    pub fn cosh(&self) -> Self {
+        self.chain(self.data.cosh(), self.data.sinh())
+    }
+
+    pub fn recip(&self) -> Self {
+        self.chain(self.data.recip(), -1. / (self.data * self.data))
+    }
+}
+
+impl_op_commutative! { + |a: DualValue, b: Float| -> DualValue { DualValue::new(a.data + b, a.tangent) } }
+impl_op! { + |a: DualValue, b: DualValue| -> DualValue { DualValue::new(a.data + b.data, a.tangent + b.tangent) } }
+
+impl_op! { - |a: DualValue, b: Float| -> DualValue { DualValue::new(a.data - b, a.tangent) } }
+impl_op! { - |a: Float, b: DualValue| -> DualValue { DualValue::new(a - b.data, -b.tangent) } }
+impl_op! { - |a: DualValue, b: DualValue| -> DualValue { DualValue::new(a.data - b.data, a.tangent - b.tangent) } }
+
+impl_op_commutative! { * |a: DualValue, b: Float| -> DualValue { DualValue::new(a.data * b, a.tangent * b) } }
+impl_op! { * |a: DualValue, b: DualValue| -> DualValue {
+    DualValue::new(a.data * b.data, a.tangent * b.data + a.data * b.tangent)
+} }
+
+impl_op! { / |a: DualValue, b: Float| -> DualValue { DualValue::new(a.data / b, a.tangent / b) } }
+impl_op! { / |a: Float, b: DualValue| -> DualValue { a * b.recip() } }
+impl_op! { / |a: DualValue, b: DualValue| -> DualValue {
+    DualValue::new(a.data / b.data, (a.tangent * b.data - a.data * b.tangent) / (b.data * b.data))
+} }
+
+impl_op! { - |a: DualValue| -> DualValue { DualValue::new(-a.data, -a.tangent) } }
+
+impl From<Float> for DualValue {
+    fn from(data: Float) -> Self {
+        DualValue::constant(data)
+    }
+}
+
+impl Sum for DualValue {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(DualValue::constant(0.), |acc, v| acc + v)
+    }
+}
+
+impl Display for DualValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("DualValue[data={}, tangent={}]", self.data, self.tangent))
+    }
+}
@@ -0,0 +1,12 @@
+//! Double precision floating point type used for data and gradients, see `single` module for `f32` one.
+
+pub(crate) use std::f64::consts;
+
+/// A floating point type of values.
+pub type Float = f64;
+
+impl From<f32> for super::Value {
+    fn from(data: f32) -> Self {
+        super::Value::new(data.into())
+    }
+}
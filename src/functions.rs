@@ -0,0 +1,100 @@
+#[cfg(test)]
+#[path = "../tests/unit/functions_test.rs"]
+mod functions_test;
+
+use super::Float;
+use super::Value;
+
+/// Turns values into probabilities using numerically stable softmax.
+pub fn softmax(values: &[Value]) -> Vec<Value> {
+    softmax_with_temperature(values, 1.)
+}
+
+/// Turns values into probabilities using softmax with given temperature: higher temperature produces
+/// a smoother distribution.
+pub fn softmax_with_temperature(values: &[Value], temperature: Float) -> Vec<Value> {
+    // NOTE skip scaling of unit temperature to avoid extra nodes in the graph
+    let scaled = if temperature == 1. {
+        values.to_vec()
+    } else {
+        values.iter().map(|value| value / temperature).collect::<Vec<_>>()
+    };
+    let logsumexp = Value::logsumexp(scaled.as_slice());
+
+    scaled.iter().map(|value| (value - &logsumexp).exp()).collect()
+}
+
+/// Sums values using a balanced reduction tree which keeps the graph shallow.
+pub fn sum(values: &[Value]) -> Value {
+    match values {
+        [] => Value::new(0.),
+        [value] => value.clone(),
+        _ => {
+            let (left, right) = values.split_at(values.len() / 2);
+            sum(left) + sum(right)
+        }
+    }
+}
+
+/// Multiplies values using a balanced reduction tree which keeps the graph shallow.
+pub fn product(values: &[Value]) -> Value {
+    match values {
+        [] => Value::new(1.),
+        [value] => value.clone(),
+        _ => {
+            let (left, right) = values.split_at(values.len() / 2);
+            product(left) * product(right)
+        }
+    }
+}
+
+/// Returns arithmetic mean of values.
+pub fn mean(values: &[Value]) -> Value {
+    sum(values) / values.len() as Float
+}
+
+/// Returns dot product of two equally sized slices.
+pub fn dot(lhs: &[Value], rhs: &[Value]) -> Value {
+    assert_eq!(lhs.len(), rhs.len(), "dot product requires slices of the same length");
+
+    sum(lhs.iter().zip(rhs).map(|(l, r)| l * r).collect::<Vec<_>>().as_slice())
+}
+
+/// Returns L2 norm of values: `sqrt(sum(x_i^2))`.
+pub fn norm(values: &[Value]) -> Value {
+    sum(values.iter().map(|value| value.powi(2)).collect::<Vec<_>>().as_slice()).sqrt()
+}
+
+/// Returns index and value of the greatest element, preferring the first one on ties.
+/// This is not a differentiable operation.
+pub fn argmax(values: &[Value]) -> Option<(usize, &Value)> {
+    values.iter().enumerate().reduce(|best, item| if item.1.get_data() > best.1.get_data() { item } else { best })
+}
+
+/// Returns index and value of the smallest element, preferring the first one on ties.
+/// This is not a differentiable operation.
+pub fn argmin(values: &[Value]) -> Option<(usize, &Value)> {
+    values.iter().enumerate().reduce(|best, item| if item.1.get_data() < best.1.get_data() { item } else { best })
+}
+
+/// Returns Jacobian matrix of the function at given point: element `[i][j]` is a derivative of `i`-th
+/// output with respect to `j`-th input.
+pub fn jacobian(func: impl Fn(&[Value]) -> Vec<Value>, inputs: &[Float]) -> Vec<Vec<Float>> {
+    let mut inputs = inputs.iter().cloned().map(Value::new).collect::<Vec<_>>();
+    let outputs = func(inputs.as_slice());
+
+    outputs
+        .iter()
+        .map(|output| {
+            inputs.iter_mut().for_each(|input| input.zero_grad());
+            output.backward();
+            inputs.iter().map(|input| input.get_grad()).collect()
+        })
+        .collect()
+}
+
+/// Returns Hessian matrix of the scalar function at given point: element `[i][j]` is a second order
+/// derivative with respect to `i`-th and `j`-th inputs.
+pub fn hessian(func: impl Fn(&[Value]) -> Value, inputs: &[Float]) -> Vec<Vec<Float>> {
+    jacobian(|inputs| func(inputs).grad_graph(inputs), inputs)
+}
@@ -0,0 +1,179 @@
+#[cfg(test)]
+#[path = "../tests/unit/graph_test.rs"]
+mod graph_test;
+
+use super::Float;
+use super::{Op, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// A snapshot of the computation graph which can be inspected, stored and rebuilt later.
+/// Nodes are kept in topological order: children always precede their parents and the root is the last one.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+}
+
+/// A node of the graph snapshot: children are referenced by their indices in the graph.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphNode {
+    pub op: Op,
+    pub data: Float,
+    pub grad: Float,
+    pub label: Option<String>,
+    pub children: Vec<usize>,
+}
+
+/// Statistics of the graph which has a given value as its root.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphStats {
+    /// Total number of nodes.
+    pub nodes: usize,
+    /// Maximum number of nodes on a path from the root to a leaf.
+    pub depth: usize,
+    /// Number of nodes without children.
+    pub leaves: usize,
+    /// Number of nodes per operation name.
+    pub ops: BTreeMap<String, usize>,
+}
+
+impl GraphNode {
+    fn title(&self) -> String {
+        let name = match (self.label.as_ref(), &self.op) {
+            (Some(label), Op::Leaf) => label.clone(),
+            (Some(label), op) => format!("{label} = {op}"),
+            (None, op) => op.to_string(),
+        };
+
+        format!("{name} | data {:.4} | grad {:.4}", self.data, self.grad)
+    }
+}
+
+impl GraphStats {
+    pub(crate) fn from_value(value: &Value) -> Self {
+        let topo = value.topological_order();
+
+        let mut depths: HashMap<_, usize> = HashMap::with_capacity(topo.len());
+        let mut ops = BTreeMap::new();
+        topo.iter().for_each(|v| {
+            let depth = 1 + v.children.iter().map(|child| depths[&child.node_ptr()]).max().unwrap_or(0);
+            depths.insert(v.node_ptr(), depth);
+            *ops.entry(v.op().to_string()).or_insert(0) += 1;
+        });
+
+        Self {
+            nodes: topo.len(),
+            depth: depths[&value.node_ptr()],
+            leaves: topo.iter().filter(|v| v.children.is_empty()).count(),
+            ops,
+        }
+    }
+}
+
+impl Graph {
+    /// Creates a snapshot of the graph which has given value as its root.
+    pub fn from_value(value: &Value) -> Self {
+        let topo = value.topological_order();
+        let indices = topo.iter().enumerate().map(|(idx, v)| (v.node_ptr(), idx)).collect::<HashMap<_, _>>();
+
+        let nodes = topo
+            .iter()
+            .map(|v| GraphNode {
+                op: v.op().clone(),
+                data: v.get_data(),
+                grad: v.get_grad(),
+                label: v.get_label(),
+                children: v.children.iter().map(|child| indices[&child.node_ptr()]).collect(),
+            })
+            .collect();
+
+        Self { nodes }
+    }
+
+    /// Rebuilds the graph returning its root. Data, gradients and labels are restored, so the result
+    /// can be used for further computations or backward. A graph cannot be rebuilt if it has:
+    /// - checkpoint or custom operation nodes: their functions are not stored in the graph
+    /// - operation nodes without children, e.g. created inside `no_grad` or a stopped `Tape`
+    pub fn to_value(&self) -> Result<Value, String> {
+        let mut values: Vec<Value> = Vec::with_capacity(self.nodes.len());
+
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let children = node
+                .children
+                .iter()
+                .map(|&child| values.get(child).cloned().ok_or_else(|| format!("node {idx} has invalid child {child}")))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let mut value = match node.op {
+                Op::Leaf => Value::new(node.data),
+                Op::Constant => Value::constant(node.data),
+                Op::Checkpoint => return Err(format!("cannot rebuild checkpoint node {idx}: its function is unknown")),
+                Op::Custom { ref name } => {
+                    return Err(format!("cannot rebuild custom '{name}' node {idx}: its function is unknown"))
+                }
+                _ if children.is_empty() => {
+                    return Err(format!(
+                        "cannot rebuild '{}' node {idx}: it has no children (recorded without grad)",
+                        node.op
+                    ))
+                }
+                _ => node
+                    .op
+                    .apply(children.as_slice())
+                    .ok_or_else(|| format!("cannot rebuild '{}' node {idx}", node.op))?,
+            };
+
+            value.set_grad(node.grad);
+            if let Some(label) = node.label.as_ref() {
+                value.set_label(label.as_str());
+            }
+
+            values.push(value);
+        }
+
+        values.pop().ok_or_else(|| "graph is empty".to_string())
+    }
+
+    /// Renders the graph in DOT format of Graphviz.
+    pub fn to_dot(&self) -> String {
+        let nodes =
+            self.nodes.iter().enumerate().map(|(idx, node)| {
+                format!("    n{idx} [shape=box, label=\"{}\"];\n", node.title().replace('"', "\\\""))
+            });
+
+        format!("digraph {{\n    rankdir=LR;\n{}{}}}\n", nodes.collect::<String>(), self.edges(" -> ", ";"))
+    }
+
+    /// Renders the graph as a Mermaid flowchart which can be embedded into markdown.
+    pub fn to_mermaid(&self) -> String {
+        let nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| format!("    n{idx}[\"{}\"]\n", node.title().replace('"', "#quot;")));
+
+        format!("flowchart LR\n{}{}", nodes.collect::<String>(), self.edges(" --> ", ""))
+    }
+
+    fn edges(&self, arrow: &str, terminator: &str) -> String {
+        self.nodes
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, node)| node.children.iter().map(move |child| (*child, idx)))
+            .map(|(child, parent)| format!("    n{child}{arrow}n{parent}{terminator}\n"))
+            .collect()
+    }
+
+    /// Serializes the graph into JSON.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Deserializes the graph from JSON.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
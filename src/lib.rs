@@ -1,15 +1,41 @@
+//! A tiny scalar valued autograd engine with a small neural networks library on top of it.
+//!
+//! The crate root uses `f64` values which can be used only within one thread. Other variants of the same
+//! API are available as separate modules behind additive features: `single` (`f32` feature) uses `f32`
+//! for data and gradients, `sync` (`sync` feature) provides values which can be shared between threads.
+
+mod compiled;
+pub use self::compiled::CompiledGraph;
+
+mod dual;
+pub use self::dual::DualValue;
+
+mod float;
+pub use self::float::Float;
+
+mod functions;
+pub use self::functions::*;
+
+mod graph;
+pub use self::graph::{Graph, GraphNode, GraphStats};
+
 mod modules;
 pub use self::modules::*;
 
+mod shared;
+pub use self::shared::MaybeSync;
+
 mod value;
-pub use self::value::Value;
+pub use self::value::{checkpoint, is_grad_enabled, jvp, new_value, no_grad, vjp, GradMode, IdMap, Op, Tape, Value};
 
-// TODO add prelude
+// NOTE unit tests of shared sources use double precision, so single precision variant is tested by
+// `tests/single_test.rs` integration test instead
+#[cfg(all(feature = "f32", not(test)))]
+pub mod single;
+#[cfg(all(feature = "f32", not(test)))]
+pub use self::single::Value as Value32;
 
-pub fn new_mlp(nin: usize, nouts: &[usize]) -> MLP {
-    MLP::new(nin, nouts)
-}
+#[cfg(feature = "sync")]
+pub mod sync;
 
-pub fn new_value(data: f64) -> Value {
-    Value::new(data)
-}
+// TODO add prelude
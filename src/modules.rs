@@ -2,45 +2,349 @@
 #[path = "../tests/unit/modules_test.rs"]
 mod modules_test;
 
-use crate::Value;
+#[cfg(feature = "onnx")]
+#[path = "modules/onnx.rs"]
+mod onnx;
+
+use super::float::consts;
+use super::shared::{ActivationFn, Shared};
+use super::{Float, IdMap, MaybeSync, Value};
 use rand::Rng;
-use std::fmt::{Display, Formatter};
+use std::collections::HashSet;
+use std::fmt::{Debug, Display, Formatter};
 use std::iter::once;
+use std::num::NonZeroUsize;
 
 pub trait Module: Display {
     fn zero_grad(&mut self);
     fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_>;
     fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_>;
+
+    /// Returns parameters together with their stable names which follow module structure,
+    /// e.g. `layers.1.neurons.3.w.0`. By default, parameters are named by their index.
+    fn named_parameters(&self) -> Box<dyn Iterator<Item = (String, &Value)> + '_> {
+        Box::new(self.parameters().enumerate().map(|(idx, p)| (idx.to_string(), p)))
+    }
+
+    /// Returns data of parameters together with their names in the order of `named_parameters`.
+    fn state_dict(&self) -> Vec<(String, Float)> {
+        self.named_parameters().map(|(name, p)| (name, p.get_data())).collect()
+    }
+
+    /// Loads data of parameters returned by `state_dict` of an architecturally identical module.
+    /// Nothing is changed if number or names of parameters do not match.
+    fn load_state_dict(&mut self, state: &[(String, Float)]) -> Result<(), String> {
+        let names = self.named_parameters().map(|(name, _)| name).collect::<Vec<_>>();
+        if names.len() != state.len() {
+            return Err(format!("expected {} parameters, got {}", names.len(), state.len()));
+        }
+
+        if let Some((expected, (actual, _))) =
+            names.iter().zip(state).find(|(expected, (actual, _))| expected != &actual)
+        {
+            return Err(format!("expected parameter '{expected}', got '{actual}'"));
+        }
+
+        self.parameters_mut().zip(state).for_each(|(p, (_, data))| p.set_data(*data));
+
+        Ok(())
+    }
+
+    /// Returns groups of parameters which can be optimized with own hyperparameters. By default,
+    /// all parameters form a single group, containers split them per part (e.g. per layer of MLP).
+    fn param_groups(&self) -> Vec<ParamGroup> {
+        vec![ParamGroup::new("default", self.parameters())]
+    }
+
+    /// Returns two groups: `weights` and `biases`, e.g. to apply weight decay to weights only.
+    fn weight_bias_groups(&self) -> Vec<ParamGroup> {
+        let (biases, weights): (Vec<_>, Vec<_>) =
+            self.named_parameters().partition(|(name, _)| name == "b" || name.ends_with(".b"));
+
+        vec![
+            ParamGroup::new("weights", weights.into_iter().map(|(_, p)| p)),
+            ParamGroup::new("biases", biases.into_iter().map(|(_, p)| p)),
+        ]
+    }
+
+    /// Returns statistics of absolute gradients of parameters per part of the module (e.g. per layer of MLP)
+    /// to diagnose vanishing or exploding gradients after backward.
+    fn grad_report(&self) -> Vec<GradStats> {
+        vec![GradStats::new(self.parameters())]
+    }
+
+    /// Switches between training and evaluation behavior, e.g. dropout is disabled during evaluation.
+    fn set_training(&mut self, _is_training: bool) {}
+
+    /// Switches the module to training mode.
+    fn train(&mut self) {
+        self.set_training(true)
+    }
+
+    /// Switches the module to evaluation mode.
+    fn eval(&mut self) {
+        self.set_training(false)
+    }
+}
+
+/// A module which maps a slice of values to a vector of values, so it can be stacked with others
+/// or used in generic code. Modules with more specific inputs (e.g. recurrent cells) document
+/// how they interpret the slice.
+pub trait Forward {
+    fn forward(&self, x: &[Value]) -> Vec<Value>;
+}
+
+/// A module which can be used inside containers like `Sequential`.
+pub trait ForwardModule: Module + Forward {}
+
+impl<T: Module + Forward> ForwardModule for T {}
+
+/// Skips parameters which were already visited, so shared (tied) parameters are returned only once.
+fn unique<'a, T: 'a>(
+    parameters: impl Iterator<Item = T> + 'a,
+    id: impl Fn(&T) -> NonZeroUsize + 'a,
+) -> impl Iterator<Item = T> + 'a {
+    let mut visited = HashSet::new();
+    parameters.filter(move |p| visited.insert(id(p)))
+}
+
+/// Returns a copy of the parameter made once per node, so shared (tied) parameters stay shared between copies.
+fn clone_parameter(parameter: &Value, copies: &mut IdMap<Value>) -> Value {
+    if let Some(copy) = copies.get(parameter) {
+        return copy.clone();
+    }
+
+    let copy = parameter.deep_clone();
+    copies.insert(parameter, copy.clone());
+
+    copy
+}
+
+/// Returns named parameters of a nested module with names prepended by `prefix`.
+fn prefixed<'a, M: Module + ?Sized>(prefix: String, module: &'a M) -> impl Iterator<Item = (String, &'a Value)> + 'a {
+    module.named_parameters().map(move |(name, p)| (format!("{prefix}.{name}"), p))
+}
+
+/// Returns named parameters of modules in a collection, e.g. `neurons.0.w.1`.
+fn prefixed_all<'a, M: Module + 'a>(
+    field: &'a str,
+    modules: impl Iterator<Item = &'a M> + 'a,
+) -> impl Iterator<Item = (String, &'a Value)> + 'a {
+    modules.enumerate().flat_map(move |(idx, module)| prefixed(format!("{field}.{idx}"), module))
 }
 
+/// A group of parameters with own hyperparameters, so optimizers and schedulers can treat them differently.
+/// Hyperparameters which are not set should be taken from optimizer's defaults.
 #[derive(Clone, Debug)]
+pub struct ParamGroup {
+    pub name: String,
+    pub parameters: Vec<Value>,
+    pub learning_rate: Option<Float>,
+    pub weight_decay: Option<Float>,
+}
+
+impl ParamGroup {
+    /// Creates a new group of parameters which share underlying data with the module's ones.
+    pub fn new<'a>(name: &str, parameters: impl Iterator<Item = &'a Value>) -> Self {
+        Self {
+            name: name.to_string(),
+            parameters: parameters.cloned().collect(),
+            learning_rate: None,
+            weight_decay: None,
+        }
+    }
+
+    pub fn with_learning_rate(mut self, learning_rate: Float) -> Self {
+        self.learning_rate = Some(learning_rate);
+        self
+    }
+
+    pub fn with_weight_decay(mut self, weight_decay: Float) -> Self {
+        self.weight_decay = Some(weight_decay);
+        self
+    }
+}
+
+/// Statistics of absolute gradients of a group of parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GradStats {
+    pub count: usize,
+    pub min: Float,
+    pub max: Float,
+    pub mean: Float,
+}
+
+impl GradStats {
+    pub fn new<'a>(parameters: impl Iterator<Item = &'a Value>) -> Self {
+        let (count, min, max, sum) = parameters.map(|p| p.get_grad().abs()).fold(
+            (0, Float::INFINITY, 0., 0.),
+            |(count, min, max, sum): (usize, Float, Float, Float), grad| {
+                (count + 1, min.min(grad), max.max(grad), sum + grad)
+            },
+        );
+
+        if count == 0 {
+            Self { count, min: 0., max: 0., mean: 0. }
+        } else {
+            Self { count, min, max, mean: sum / count as Float }
+        }
+    }
+}
+
+impl Display for GradStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "GradStats[count={}, min={}, max={}, mean={}]",
+            self.count, self.min, self.max, self.mean
+        ))
+    }
+}
+
+#[derive(Clone)]
 pub enum NeuronType {
     Linear,
     ReLU,
+    Tanh,
+    Sigmoid,
+    /// User defined activation, see `NeuronType::custom`.
+    Custom(ActivationFn),
+}
+
+impl NeuronType {
+    /// Creates neuron type with given activation function, e.g. `NeuronType::custom(|x| x.silu())`.
+    pub fn custom(activation: impl Fn(Value) -> Value + MaybeSync + 'static) -> Self {
+        NeuronType::Custom(Shared::new(activation))
+    }
+}
+
+impl Debug for NeuronType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            NeuronType::Linear => "Linear",
+            NeuronType::ReLU => "ReLU",
+            NeuronType::Tanh => "Tanh",
+            NeuronType::Sigmoid => "Sigmoid",
+            NeuronType::Custom(_) => "Custom",
+        })
+    }
+}
+
+/// Specifies how weights are initialized, biases are always initialized with zeros.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Init {
+    /// Uniform distribution in given range.
+    Uniform { low: Float, high: Float },
+    /// Normal distribution with given mean and standard deviation.
+    Normal { mean: Float, std: Float },
+    /// Xavier (Glorot) uniform initialization suited for tanh and sigmoid activations.
+    Xavier,
+    /// He (Kaiming) normal initialization suited for ReLU activation.
+    He,
+    /// All weights are zero.
+    Zeros,
+}
+
+impl Default for Init {
+    fn default() -> Self {
+        Init::Uniform { low: -1., high: 1. }
+    }
+}
+
+impl Init {
+    /// Samples a weight of a unit with given number of inputs and outputs.
+    pub fn sample(&self, rng: &mut (impl Rng + ?Sized), fan_in: usize, fan_out: usize) -> Float {
+        let mut normal = |mean: Float, std: Float| {
+            // Box-Muller transform
+            let (u1, u2): (Float, Float) = (1. - rng.gen::<Float>(), rng.gen());
+            mean + std * (-2. * u1.ln()).sqrt() * (2. * consts::PI * u2).cos()
+        };
+
+        match *self {
+            Init::Normal { mean, std } => normal(mean, std),
+            Init::He => normal(0., (2. / fan_in.max(1) as Float).sqrt()),
+            Init::Uniform { low, high } if low < high => rng.gen_range(low..high),
+            Init::Uniform { low, .. } => low,
+            Init::Xavier => {
+                let limit = (6. / (fan_in + fan_out).max(1) as Float).sqrt();
+                rng.gen_range(-limit..limit)
+            }
+            Init::Zeros => 0.,
+        }
+    }
+}
+
+fn init_weights(fan_in: usize, fan_out: usize, init: Init, rng: &mut (impl Rng + ?Sized)) -> Vec<Value> {
+    (0..fan_in).map(|_| Value::new(init.sample(rng, fan_in, fan_out))).collect()
 }
 
 #[derive(Debug)]
 pub struct Neuron {
     w: Vec<Value>,
-    b: Value,
+    b: Option<Value>,
     ntype: NeuronType,
 }
 
 impl Neuron {
-    pub(crate) fn new(nin: usize, ntype: NeuronType) -> Self {
-        let mut rng = rand::thread_rng();
+    pub fn new(nin: usize, ntype: NeuronType) -> Self {
+        Self::new_with_rng(nin, ntype, &mut rand::thread_rng())
+    }
+
+    /// Creates neuron using given random number generator, e.g. a seeded one to get reproducible results.
+    pub fn new_with_rng(nin: usize, ntype: NeuronType, rng: &mut (impl Rng + ?Sized)) -> Self {
+        Self { w: init_weights(nin, 1, Init::default(), rng), b: Some(Value::new(0.)), ntype }
+    }
+
+    /// Creates neuron from given parameters which can be shared with other modules (tied weights),
+    /// so their gradients accumulate into the same values.
+    pub fn from_parameters(w: Vec<Value>, b: Option<Value>, ntype: NeuronType) -> Self {
+        Self { w, b, ntype }
+    }
+
+    /// Returns neuron which weights are reinitialized using given strategy and bias is set to zero.
+    pub fn with_init(self, init: Init) -> Self {
+        self.with_init_rng(init, &mut rand::thread_rng())
+    }
+
+    /// Same as `with_init`, but uses given random number generator.
+    pub fn with_init_rng(self, init: Init, rng: &mut (impl Rng + ?Sized)) -> Self {
+        self.with_init_of(init, 1, rng)
+    }
+
+    fn with_init_of(self, init: Init, fan_out: usize, rng: &mut (impl Rng + ?Sized)) -> Self {
+        Self { w: init_weights(self.w.len(), fan_out, init, rng), b: self.b.map(|_| Value::new(0.)), ntype: self.ntype }
+    }
+
+    /// Returns neuron with or without bias: a neuron without bias computes a weighted sum only.
+    pub fn with_bias(mut self, bias: bool) -> Self {
+        self.b = match (self.b, bias) {
+            (None, true) => Some(Value::new(0.)),
+            (b, true) => b,
+            (_, false) => None,
+        };
+        self
+    }
+
+    fn clone_with(&self, copies: &mut IdMap<Value>) -> Self {
         Self {
-            w: (0..nin).map(|_| rng.gen_range(-1.0..1.0)).map(|data| Value::new(data)).collect(),
-            b: Value::new(0.),
-            ntype,
+            w: self.w.iter().map(|w| clone_parameter(w, copies)).collect(),
+            b: self.b.as_ref().map(|b| clone_parameter(b, copies)),
+            ntype: self.ntype.clone(),
         }
     }
 
     pub fn call(&self, x: &[Value]) -> Value {
-        let act = self.w.iter().zip(x).map(|(wi, xi)| wi * xi).sum::<Value>() + &self.b;
-        match self.ntype {
+        let mut terms = self.w.iter().zip(x);
+        let init = match self.b.as_ref() {
+            Some(b) => b.clone(),
+            None => terms.next().map_or_else(|| Value::constant(0.), |(wi, xi)| wi * xi),
+        };
+        let act = terms.fold(init, |acc, (wi, xi)| Value::fma(wi, xi, &acc));
+        match &self.ntype {
             NeuronType::Linear => act,
             NeuronType::ReLU => act.relu(),
+            NeuronType::Tanh => act.tanh(),
+            NeuronType::Sigmoid => act.sigmoid(),
+            NeuronType::Custom(activation) => activation(act),
         }
     }
 }
@@ -51,21 +355,35 @@ impl Module for Neuron {
     }
 
     fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
-        Box::new(self.w.iter().chain(once(&self.b)))
+        Box::new(self.w.iter().chain(self.b.iter()))
     }
 
     fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
-        Box::new(self.w.iter_mut().chain(once(&mut self.b)))
+        Box::new(self.w.iter_mut().chain(self.b.iter_mut()))
+    }
+
+    fn named_parameters(&self) -> Box<dyn Iterator<Item = (String, &Value)> + '_> {
+        let w = self.w.iter().enumerate().map(|(idx, w)| (format!("w.{idx}"), w));
+        Box::new(w.chain(self.b.iter().map(|b| ("b".to_string(), b))))
+    }
+}
+
+/// Parameters are copied into new storage, so the copy can be trained independently.
+impl Clone for Neuron {
+    fn clone(&self) -> Self {
+        self.clone_with(&mut IdMap::new())
+    }
+}
+
+impl Forward for Neuron {
+    fn forward(&self, x: &[Value]) -> Vec<Value> {
+        vec![self.call(x)]
     }
 }
 
 impl Display for Neuron {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let ntype = match self.ntype {
-            NeuronType::ReLU => "ReLU",
-            NeuronType::Linear => "Linear",
-        };
-        f.write_fmt(format_args!("{}Neuron({})", ntype, self.w.len()))
+        f.write_fmt(format_args!("{:?}Neuron({})", self.ntype, self.w.len()))
     }
 }
 
@@ -75,13 +393,118 @@ pub struct Layer {
 }
 
 impl Layer {
-    pub(crate) fn new(nin: usize, nout: usize, ntype: NeuronType) -> Self {
-        Self { neurons: (0..nout).map(|_| Neuron::new(nin, ntype.clone())).collect() }
+    pub fn new(nin: usize, nout: usize, ntype: NeuronType) -> Self {
+        Self::new_with_rng(nin, nout, ntype, &mut rand::thread_rng())
+    }
+
+    /// Creates layer using given random number generator, e.g. a seeded one to get reproducible results.
+    pub fn new_with_rng(nin: usize, nout: usize, ntype: NeuronType, rng: &mut (impl Rng + ?Sized)) -> Self {
+        Self { neurons: (0..nout).map(|_| Neuron::new_with_rng(nin, ntype.clone(), rng)).collect() }
+    }
+
+    /// Creates layer which weights are sampled once using given strategy.
+    fn new_with_init(nin: usize, nout: usize, ntype: NeuronType, init: Init, rng: &mut (impl Rng + ?Sized)) -> Self {
+        let neurons = (0..nout).map(|_| Neuron {
+            w: init_weights(nin, nout, init, rng),
+            b: Some(Value::new(0.)),
+            ntype: ntype.clone(),
+        });
+        Self { neurons: neurons.collect() }
+    }
+
+    pub fn from_neurons(neurons: Vec<Neuron>) -> Self {
+        Self { neurons }
+    }
+
+    /// Returns layer which shares all parameters with this one, unlike `clone` which copies them.
+    pub fn tied(&self) -> Self {
+        let neurons = self
+            .neurons
+            .iter()
+            .map(|neuron| Neuron::from_parameters(neuron.w.clone(), neuron.b.clone(), neuron.ntype.clone()));
+        Self { neurons: neurons.collect() }
+    }
+
+    /// Returns layer of `nin` neurons which share transposed weights of this one and have own zero biases,
+    /// e.g. a decoder tied to an encoder.
+    pub fn tied_transposed(&self, ntype: NeuronType) -> Self {
+        let nin = self.neurons.first().map_or(0, |neuron| neuron.w.len());
+        let neurons = (0..nin).map(|idx| {
+            let w = self.neurons.iter().map(|neuron| neuron.w[idx].clone()).collect();
+            Neuron::from_parameters(w, Some(Value::new(0.)), ntype.clone())
+        });
+
+        Self { neurons: neurons.collect() }
+    }
+
+    /// Returns layer which parameters are reinitialized using given strategy.
+    pub fn with_init(self, init: Init) -> Self {
+        self.with_init_rng(init, &mut rand::thread_rng())
+    }
+
+    /// Same as `with_init`, but uses given random number generator.
+    pub fn with_init_rng(self, init: Init, rng: &mut (impl Rng + ?Sized)) -> Self {
+        let fan_out = self.neurons.len();
+        Self { neurons: self.neurons.into_iter().map(|neuron| neuron.with_init_of(init, fan_out, rng)).collect() }
+    }
+
+    /// Returns layer which neurons have or do not have bias.
+    pub fn with_bias(self, bias: bool) -> Self {
+        Self { neurons: self.neurons.into_iter().map(|neuron| neuron.with_bias(bias)).collect() }
+    }
+
+    fn clone_with(&self, copies: &mut IdMap<Value>) -> Self {
+        Self { neurons: self.neurons.iter().map(|neuron| neuron.clone_with(copies)).collect() }
     }
 
     pub fn call(&self, x: &[Value]) -> Vec<Value> {
         self.neurons.iter().map(|neuron| neuron.call(x)).collect()
     }
+
+    /// Returns weight matrix of `[nout, nin]` shape: one row per neuron.
+    pub fn weights(&self) -> Vec<Vec<Float>> {
+        self.neurons.iter().map(|neuron| neuron.w.iter().map(|w| w.get_data()).collect()).collect()
+    }
+
+    /// Sets weight matrix of `[nout, nin]` shape: one row per neuron. Nothing is changed if shape does not match.
+    pub fn set_weights(&mut self, weights: &[Vec<Float>]) -> Result<(), String> {
+        let (nout, nin) = (self.neurons.len(), self.neurons.first().map_or(0, |neuron| neuron.w.len()));
+        if weights.len() != nout
+            || weights.iter().zip(self.neurons.iter()).any(|(row, neuron)| row.len() != neuron.w.len())
+        {
+            return Err(format!("expected weight matrix of [{nout}, {nin}] shape"));
+        }
+
+        self.neurons.iter_mut().zip(weights).for_each(|(neuron, row)| {
+            neuron.w.iter_mut().zip(row).for_each(|(w, &data)| w.set_data(data));
+        });
+
+        Ok(())
+    }
+
+    /// Returns bias of each neuron, zero is used for neurons without bias.
+    pub fn biases(&self) -> Vec<Float> {
+        self.neurons.iter().map(|neuron| neuron.b.as_ref().map_or(0., |b| b.get_data())).collect()
+    }
+
+    /// Sets bias of each neuron. Nothing is changed if size does not match or neurons have no bias.
+    pub fn set_biases(&mut self, biases: &[Float]) -> Result<(), String> {
+        if biases.len() != self.neurons.len() {
+            return Err(format!("expected {} biases, got {}", self.neurons.len(), biases.len()));
+        }
+
+        if self.neurons.iter().any(|neuron| neuron.b.is_none()) {
+            return Err("layer has neurons without bias".to_string());
+        }
+
+        self.neurons
+            .iter_mut()
+            .filter_map(|neuron| neuron.b.as_mut())
+            .zip(biases)
+            .for_each(|(b, &data)| b.set_data(data));
+
+        Ok(())
+    }
 }
 
 impl Module for Layer {
@@ -90,11 +513,28 @@ impl Module for Layer {
     }
 
     fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
-        Box::new(self.neurons.iter().flat_map(|neuron| neuron.parameters()))
+        Box::new(unique(self.neurons.iter().flat_map(|neuron| neuron.parameters()), |p| p.id()))
     }
 
     fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
-        Box::new(self.neurons.iter_mut().flat_map(|neuron| neuron.parameters_mut()))
+        Box::new(unique(self.neurons.iter_mut().flat_map(|neuron| neuron.parameters_mut()), |p| p.id()))
+    }
+
+    fn named_parameters(&self) -> Box<dyn Iterator<Item = (String, &Value)> + '_> {
+        Box::new(unique(prefixed_all("neurons", self.neurons.iter()), |(_, p)| p.id()))
+    }
+}
+
+/// Parameters are copied into new storage, parameters shared between neurons stay shared in the copy.
+impl Clone for Layer {
+    fn clone(&self) -> Self {
+        self.clone_with(&mut IdMap::new())
+    }
+}
+
+impl Forward for Layer {
+    fn forward(&self, x: &[Value]) -> Vec<Value> {
+        self.call(x)
     }
 }
 
@@ -113,19 +553,67 @@ pub struct MLP {
 }
 
 impl MLP {
-    pub(crate) fn new(nin: usize, nouts: &[usize]) -> Self {
+    /// Creates MLP with given activation of hidden layers, the last layer is linear.
+    pub(crate) fn new(nin: usize, nouts: &[usize], activation: NeuronType) -> Self {
+        Self::new_with_rng(nin, nouts, activation, &mut rand::thread_rng())
+    }
+
+    /// Creates MLP using given random number generator, e.g. a seeded one to get reproducible results.
+    pub fn new_with_rng(nin: usize, nouts: &[usize], activation: NeuronType, rng: &mut (impl Rng + ?Sized)) -> Self {
+        let activations = (0..nouts.len())
+            .map(|idx| if idx != (nouts.len() - 1) { activation.clone() } else { NeuronType::Linear })
+            .collect::<Vec<_>>();
+
+        Self::from_layer_sizes(nin, nouts, activations.as_slice(), rng)
+    }
+
+    /// Creates MLP with given activation of each layer, including the last one. Fails if amount of
+    /// activations does not match amount of layers. See also `MLP::builder` to configure layers one by one.
+    pub fn new_with_activations(
+        nin: usize,
+        nouts: &[usize],
+        activations: &[NeuronType],
+        rng: &mut (impl Rng + ?Sized),
+    ) -> Result<Self, String> {
+        if nouts.len() != activations.len() {
+            return Err(format!("expected {} activations, got {}", nouts.len(), activations.len()));
+        }
+
+        Ok(Self::from_layer_sizes(nin, nouts, activations, rng))
+    }
+
+    fn from_layer_sizes(
+        nin: usize,
+        nouts: &[usize],
+        activations: &[NeuronType],
+        rng: &mut (impl Rng + ?Sized),
+    ) -> Self {
         let sz = once(nin).chain(nouts.iter().cloned()).collect::<Vec<_>>();
 
         Self {
-            layers: (0..nouts.len())
-                .map(|idx| {
-                    let ntype = if idx != (nouts.len() - 1) { NeuronType::ReLU } else { NeuronType::Linear };
-                    Layer::new(sz[idx], sz[idx + 1], ntype)
-                })
+            layers: activations
+                .iter()
+                .enumerate()
+                .map(|(idx, ntype)| Layer::new_with_rng(sz[idx], sz[idx + 1], ntype.clone(), rng))
                 .collect(),
         }
     }
 
+    /// Returns builder to configure MLP layer by layer.
+    pub fn builder() -> MLPBuilder {
+        MLPBuilder::default()
+    }
+
+    /// Returns MLP which parameters are reinitialized using given strategy.
+    pub fn with_init(self, init: Init) -> Self {
+        self.with_init_rng(init, &mut rand::thread_rng())
+    }
+
+    /// Same as `with_init`, but uses given random number generator.
+    pub fn with_init_rng(self, init: Init, rng: &mut (impl Rng + ?Sized)) -> Self {
+        Self { layers: self.layers.into_iter().map(|layer| layer.with_init_rng(init, rng)).collect() }
+    }
+
     pub fn call(&self, x: &[Value]) -> Vec<Value> {
         let mut iterator = self.layers.iter();
         iterator
@@ -135,17 +623,133 @@ impl MLP {
     }
 }
 
+pub fn new_mlp(nin: usize, nouts: &[usize]) -> MLP {
+    MLP::new(nin, nouts, NeuronType::ReLU)
+}
+
+/// Creates MLP which parameters are initialized from given seed, so results are reproducible.
+pub fn new_mlp_with_seed(nin: usize, nouts: &[usize], seed: u64) -> MLP {
+    use rand::SeedableRng;
+    MLP::new_with_rng(nin, nouts, NeuronType::ReLU, &mut rand::rngs::StdRng::seed_from_u64(seed))
+}
+
+pub fn new_mlp_with_activation(nin: usize, nouts: &[usize], activation: NeuronType) -> MLP {
+    MLP::new(nin, nouts, activation)
+}
+
+/// Creates MLP with given activation of each layer, e.g. `&[NeuronType::Tanh, NeuronType::Tanh, NeuronType::Linear]`.
+/// Fails if amount of activations does not match amount of layers.
+pub fn new_mlp_with_activations(nin: usize, nouts: &[usize], activations: &[NeuronType]) -> Result<MLP, String> {
+    MLP::new_with_activations(nin, nouts, activations, &mut rand::thread_rng())
+}
+
+/// Configures MLP, e.g. `MLP::builder().input(2).hidden(16, NeuronType::ReLU).output(1).seed(42).build()?`.
+#[derive(Debug, Default)]
+pub struct MLPBuilder {
+    nin: Option<usize>,
+    layers: Vec<(usize, NeuronType)>,
+    init: Option<Init>,
+    bias: Option<bool>,
+    seed: Option<u64>,
+}
+
+impl MLPBuilder {
+    /// Sets number of inputs.
+    pub fn input(mut self, nin: usize) -> Self {
+        self.nin = Some(nin);
+        self
+    }
+
+    /// Adds a hidden layer of given size and activation.
+    pub fn hidden(mut self, nout: usize, activation: NeuronType) -> Self {
+        self.layers.push((nout, activation));
+        self
+    }
+
+    /// Adds a linear output layer of given size.
+    pub fn output(self, nout: usize) -> Self {
+        self.hidden(nout, NeuronType::Linear)
+    }
+
+    /// Sets initialization strategy of weights, uniform in `[-1, 1)` range is used by default.
+    pub fn init(mut self, init: Init) -> Self {
+        self.init = Some(init);
+        self
+    }
+
+    /// Sets whether neurons have bias, they have by default.
+    pub fn bias(mut self, bias: bool) -> Self {
+        self.bias = Some(bias);
+        self
+    }
+
+    /// Sets seed of random number generator to get reproducible parameters.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Builds MLP, fails if number of inputs is not specified.
+    pub fn build(self) -> Result<MLP, String> {
+        use rand::SeedableRng;
+
+        let nin = self.nin.ok_or_else(|| "number of inputs should be specified".to_string())?;
+        let mut rng = match self.seed {
+            Some(seed) => rand::rngs::StdRng::seed_from_u64(seed),
+            None => rand::rngs::StdRng::from_rng(rand::thread_rng()).expect("cannot create random number generator"),
+        };
+        let (init, bias) = (self.init.unwrap_or_default(), self.bias.unwrap_or(true));
+
+        let sizes = once(nin).chain(self.layers.iter().map(|(nout, _)| *nout)).collect::<Vec<_>>();
+        let layers = self
+            .layers
+            .into_iter()
+            .zip(sizes.windows(2))
+            .map(|((_, ntype), sizes)| Layer::new_with_init(sizes[0], sizes[1], ntype, init, &mut rng).with_bias(bias))
+            .collect();
+
+        Ok(MLP { layers })
+    }
+}
+
 impl Module for MLP {
     fn zero_grad(&mut self) {
         self.parameters_mut().for_each(|p| p.zero_grad())
     }
 
     fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
-        Box::new(self.layers.iter().flat_map(|layer| layer.parameters()))
+        Box::new(unique(self.layers.iter().flat_map(|layer| layer.parameters()), |p| p.id()))
     }
 
     fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
-        Box::new(self.layers.iter_mut().flat_map(|layer| layer.parameters_mut()))
+        Box::new(unique(self.layers.iter_mut().flat_map(|layer| layer.parameters_mut()), |p| p.id()))
+    }
+
+    fn named_parameters(&self) -> Box<dyn Iterator<Item = (String, &Value)> + '_> {
+        Box::new(unique(prefixed_all("layers", self.layers.iter()), |(_, p)| p.id()))
+    }
+
+    fn param_groups(&self) -> Vec<ParamGroup> {
+        let groups = self.layers.iter().enumerate();
+        groups.map(|(idx, layer)| ParamGroup::new(&format!("layers.{idx}"), layer.parameters())).collect()
+    }
+
+    fn grad_report(&self) -> Vec<GradStats> {
+        self.layers.iter().map(|layer| GradStats::new(layer.parameters())).collect()
+    }
+}
+
+impl Forward for MLP {
+    fn forward(&self, x: &[Value]) -> Vec<Value> {
+        self.call(x)
+    }
+}
+
+/// Parameters are copied into new storage, parameters shared between layers (tied) stay shared in the copy.
+impl Clone for MLP {
+    fn clone(&self) -> Self {
+        let mut copies = IdMap::new();
+        Self { layers: self.layers.iter().map(|layer| layer.clone_with(&mut copies)).collect() }
     }
 }
 
@@ -156,3 +760,1303 @@ impl Display for MLP {
         f.write_fmt(format_args!("MLP of [{}]", layers))
     }
 }
+
+/// Randomly zeroes inputs with given probability during training and scales the rest by `1 / (1 - p)`,
+/// so the expected sum stays the same. Inputs are passed unchanged in evaluation mode.
+#[derive(Clone, Debug)]
+pub struct Dropout {
+    p: Float,
+    is_training: bool,
+}
+
+impl Dropout {
+    pub fn new(p: Float) -> Self {
+        assert!((0. ..1.).contains(&p), "dropout probability should be in [0, 1) range");
+        Self { p, is_training: true }
+    }
+
+    pub fn call(&self, x: &[Value]) -> Vec<Value> {
+        self.call_with_rng(x, &mut rand::thread_rng())
+    }
+
+    /// Same as `call`, but uses given random number generator.
+    pub fn call_with_rng(&self, x: &[Value], rng: &mut (impl Rng + ?Sized)) -> Vec<Value> {
+        if !self.is_training || self.p == 0. {
+            return x.to_vec();
+        }
+
+        let scale = 1. / (1. - self.p);
+        x.iter().map(|xi| xi * if rng.gen::<Float>() < self.p { 0. } else { scale }).collect()
+    }
+}
+
+impl Module for Dropout {
+    fn zero_grad(&mut self) {}
+
+    fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
+        Box::new(std::iter::empty())
+    }
+
+    fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
+        Box::new(std::iter::empty())
+    }
+
+    fn set_training(&mut self, is_training: bool) {
+        self.is_training = is_training;
+    }
+}
+
+impl Forward for Dropout {
+    fn forward(&self, x: &[Value]) -> Vec<Value> {
+        self.call(x)
+    }
+}
+
+impl Display for Dropout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Dropout({})", self.p))
+    }
+}
+
+/// 2D convolution over an input flattened in channel, row, column order: each output channel is computed
+/// by a linear neuron (filter) sliding over all input channels. The output is flattened the same way.
+#[derive(Clone, Debug)]
+pub struct Conv2d {
+    filters: Vec<Neuron>,
+    in_channels: usize,
+    height: usize,
+    width: usize,
+    kernel: usize,
+    stride: usize,
+    padding: usize,
+}
+
+impl Conv2d {
+    /// Creates convolution of an input with given channels and height x width size using square kernel,
+    /// unit stride and no padding.
+    pub fn new(in_channels: usize, out_channels: usize, (height, width): (usize, usize), kernel: usize) -> Self {
+        Self::new_with_rng(in_channels, out_channels, (height, width), kernel, &mut rand::thread_rng())
+    }
+
+    /// Creates convolution using given random number generator.
+    pub fn new_with_rng(
+        in_channels: usize,
+        out_channels: usize,
+        (height, width): (usize, usize),
+        kernel: usize,
+        rng: &mut (impl Rng + ?Sized),
+    ) -> Self {
+        assert!(kernel > 0, "kernel size should be positive");
+        let filters = (0..out_channels)
+            .map(|_| Neuron::new_with_rng(in_channels * kernel * kernel, NeuronType::Linear, rng))
+            .collect();
+
+        Self { filters, in_channels, height, width, kernel, stride: 1, padding: 0 }
+    }
+
+    /// Returns convolution with given step between kernel positions.
+    pub fn with_stride(self, stride: usize) -> Self {
+        assert!(stride > 0, "stride should be positive");
+        Self { stride, ..self }
+    }
+
+    /// Returns convolution which input is padded with given number of zeros on each side.
+    pub fn with_padding(self, padding: usize) -> Self {
+        Self { padding, ..self }
+    }
+
+    /// Returns convolution which parameters are reinitialized using given strategy.
+    pub fn with_init(self, init: Init) -> Self {
+        self.with_init_rng(init, &mut rand::thread_rng())
+    }
+
+    /// Same as `with_init`, but uses given random number generator.
+    pub fn with_init_rng(self, init: Init, rng: &mut (impl Rng + ?Sized)) -> Self {
+        let fan_out = self.filters.len() * self.kernel * self.kernel;
+        let filters = self.filters.into_iter().map(|filter| filter.with_init_of(init, fan_out, rng)).collect();
+
+        Self { filters, ..self }
+    }
+
+    /// Returns height and width of an output channel: zero when the kernel is larger than the padded input.
+    pub fn output_size(&self) -> (usize, usize) {
+        let size = |input: usize| match (input + 2 * self.padding).checked_sub(self.kernel) {
+            Some(size) => size / self.stride + 1,
+            None => 0,
+        };
+        (size(self.height), size(self.width))
+    }
+
+    pub fn call(&self, x: &[Value]) -> Vec<Value> {
+        assert_eq!(x.len(), self.in_channels * self.height * self.width, "input size does not match configuration");
+
+        let (out_height, out_width) = self.output_size();
+        let zero = Value::constant(0.);
+        let input = |channel: usize, row: usize, col: usize| {
+            let (row, col) = (row.checked_sub(self.padding), col.checked_sub(self.padding));
+            match row.zip(col) {
+                Some((row, col)) if row < self.height && col < self.width => {
+                    &x[(channel * self.height + row) * self.width + col]
+                }
+                _ => &zero,
+            }
+        };
+
+        let patches = (0..out_height)
+            .flat_map(|row| (0..out_width).map(move |col| (row * self.stride, col * self.stride)))
+            .map(|(top, left)| {
+                (0..self.in_channels)
+                    .flat_map(|channel| {
+                        (0..self.kernel).flat_map(move |dr| (0..self.kernel).map(move |dc| (channel, dr, dc)))
+                    })
+                    .map(|(channel, dr, dc)| input(channel, top + dr, left + dc).clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        self.filters.iter().flat_map(|filter| patches.iter().map(|patch| filter.call(patch.as_slice()))).collect()
+    }
+}
+
+impl Module for Conv2d {
+    fn zero_grad(&mut self) {
+        self.parameters_mut().for_each(|p| p.zero_grad())
+    }
+
+    fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
+        Box::new(unique(self.filters.iter().flat_map(|filter| filter.parameters()), |p| p.id()))
+    }
+
+    fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
+        Box::new(unique(self.filters.iter_mut().flat_map(|filter| filter.parameters_mut()), |p| p.id()))
+    }
+
+    fn named_parameters(&self) -> Box<dyn Iterator<Item = (String, &Value)> + '_> {
+        Box::new(unique(prefixed_all("filters", self.filters.iter()), |(_, p)| p.id()))
+    }
+}
+
+impl Forward for Conv2d {
+    fn forward(&self, x: &[Value]) -> Vec<Value> {
+        self.call(x)
+    }
+}
+
+impl Display for Conv2d {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "Conv2d({}, {}, kernel={}, stride={}, padding={})",
+            self.in_channels,
+            self.filters.len(),
+            self.kernel,
+            self.stride,
+            self.padding
+        ))
+    }
+}
+
+/// Returns the selected element itself, so the gradient is routed only to it.
+fn max_pool(window: Vec<&Value>) -> Value {
+    window
+        .iter()
+        .copied()
+        .reduce(|best, item| if item.get_data() > best.get_data() { item } else { best })
+        .unwrap()
+        .clone()
+}
+
+fn avg_pool(window: Vec<&Value>) -> Value {
+    super::functions::mean(window.into_iter().cloned().collect::<Vec<_>>().as_slice())
+}
+
+macro_rules! pool_module_impl {
+    ($name: ident) => {
+        impl Module for $name {
+            fn zero_grad(&mut self) {}
+
+            fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
+                Box::new(std::iter::empty())
+            }
+
+            fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
+                Box::new(std::iter::empty())
+            }
+        }
+
+        impl Forward for $name {
+            fn forward(&self, x: &[Value]) -> Vec<Value> {
+                self.call(x)
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+                f.write_fmt(format_args!("{}(kernel={}, stride={})", stringify!($name), self.kernel, self.stride))
+            }
+        }
+    };
+}
+
+macro_rules! pool_1d_impl {
+    ($(#[$meta:meta])* $name: ident by $reduce: ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug)]
+        pub struct $name {
+            kernel: usize,
+            stride: usize,
+        }
+
+        impl $name {
+            /// Creates pooling with given window size and the same stride.
+            pub fn new(kernel: usize) -> Self {
+                assert!(kernel > 0, "kernel size should be positive");
+                Self { kernel, stride: kernel }
+            }
+
+            /// Returns pooling with given step between windows.
+            pub fn with_stride(self, stride: usize) -> Self {
+                assert!(stride > 0, "stride should be positive");
+                Self { stride, ..self }
+            }
+
+            pub fn call(&self, x: &[Value]) -> Vec<Value> {
+                let count = if x.len() < self.kernel { 0 } else { (x.len() - self.kernel) / self.stride + 1 };
+
+                (0..count).map(|idx| $reduce(x[idx * self.stride..][..self.kernel].iter().collect())).collect()
+            }
+        }
+
+        pool_module_impl! { $name }
+    };
+}
+
+macro_rules! pool_2d_impl {
+    ($(#[$meta:meta])* $name: ident by $reduce: ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug)]
+        pub struct $name {
+            channels: usize,
+            height: usize,
+            width: usize,
+            kernel: usize,
+            stride: usize,
+        }
+
+        impl $name {
+            /// Creates pooling of an input with given channels and height x width size flattened in
+            /// channel, row, column order using square window of given size and the same stride.
+            pub fn new(channels: usize, (height, width): (usize, usize), kernel: usize) -> Self {
+                assert!(kernel > 0, "kernel size should be positive");
+                Self { channels, height, width, kernel, stride: kernel }
+            }
+
+            /// Returns pooling with given step between windows.
+            pub fn with_stride(self, stride: usize) -> Self {
+                assert!(stride > 0, "stride should be positive");
+                Self { stride, ..self }
+            }
+
+            /// Returns height and width of an output channel.
+            pub fn output_size(&self) -> (usize, usize) {
+                let size = |input: usize| if input < self.kernel { 0 } else { (input - self.kernel) / self.stride + 1 };
+                (size(self.height), size(self.width))
+            }
+
+            pub fn call(&self, x: &[Value]) -> Vec<Value> {
+                assert_eq!(
+                    x.len(),
+                    self.channels * self.height * self.width,
+                    "input size does not match configuration"
+                );
+                let (out_height, out_width) = self.output_size();
+
+                (0..self.channels)
+                    .flat_map(|channel| {
+                        (0..out_height).flat_map(move |row| (0..out_width).map(move |col| (channel, row, col)))
+                    })
+                    .map(|(channel, row, col)| {
+                        let (top, left) = (row * self.stride, col * self.stride);
+                        let window = (top..top + self.kernel)
+                            .flat_map(|row| (left..left + self.kernel).map(move |col| (row, col)))
+                            .map(|(row, col)| &x[(channel * self.height + row) * self.width + col])
+                            .collect();
+                        $reduce(window)
+                    })
+                    .collect()
+            }
+        }
+
+        pool_module_impl! { $name }
+    };
+}
+
+pool_1d_impl! {
+    /// Takes maximum of each window of a sequence: gradient is routed only to the selected element.
+    MaxPool1d by max_pool
+}
+pool_1d_impl! {
+    /// Takes average of each window of a sequence.
+    AvgPool1d by avg_pool
+}
+pool_2d_impl! {
+    /// Takes maximum of each window of every channel: gradient is routed only to the selected element.
+    MaxPool2d by max_pool
+}
+pool_2d_impl! {
+    /// Takes average of each window of every channel.
+    AvgPool2d by avg_pool
+}
+
+fn unroll(xs: &[Vec<Value>], h0: Vec<Value>, cell: impl Fn(&[Value], &[Value]) -> Vec<Value>) -> Vec<Vec<Value>> {
+    xs.iter()
+        .scan(h0, |h, x| {
+            *h = cell(x.as_slice(), h.as_slice());
+            Some(h.clone())
+        })
+        .collect()
+}
+
+/// Vanilla recurrent cell computing next hidden state as `tanh(W_x * x + W_h * h + b)`.
+#[derive(Clone, Debug)]
+pub struct RNNCell {
+    layer: Layer,
+    input_size: usize,
+}
+
+impl RNNCell {
+    pub fn new(input_size: usize, hidden_size: usize) -> Self {
+        Self::new_with_rng(input_size, hidden_size, &mut rand::thread_rng())
+    }
+
+    /// Creates cell using given random number generator.
+    pub fn new_with_rng(input_size: usize, hidden_size: usize, rng: &mut (impl Rng + ?Sized)) -> Self {
+        Self { layer: Layer::new_with_rng(input_size + hidden_size, hidden_size, NeuronType::Tanh, rng), input_size }
+    }
+
+    /// Returns cell which parameters are reinitialized using given strategy.
+    pub fn with_init(self, init: Init) -> Self {
+        self.with_init_rng(init, &mut rand::thread_rng())
+    }
+
+    /// Same as `with_init`, but uses given random number generator.
+    pub fn with_init_rng(self, init: Init, rng: &mut (impl Rng + ?Sized)) -> Self {
+        Self { layer: self.layer.with_init_rng(init, rng), ..self }
+    }
+
+    /// Returns size of the hidden state.
+    pub fn hidden_size(&self) -> usize {
+        self.layer.neurons.len()
+    }
+
+    /// Computes next hidden state from given input and previous hidden state.
+    pub fn call(&self, x: &[Value], h: &[Value]) -> Vec<Value> {
+        assert_eq!(x.len(), self.input_size, "input size does not match configuration");
+        assert_eq!(h.len(), self.hidden_size(), "hidden state size does not match configuration");
+
+        self.layer.call(x.iter().chain(h.iter()).cloned().collect::<Vec<_>>().as_slice())
+    }
+
+    /// Applies the cell to a sequence of inputs starting from given hidden state (zeros if not specified)
+    /// and returns all hidden states, so backward through them implements backpropagation through time.
+    pub fn unroll(&self, xs: &[Vec<Value>], h0: Option<Vec<Value>>) -> Vec<Vec<Value>> {
+        unroll(xs, h0.unwrap_or_else(|| vec![Value::constant(0.); self.hidden_size()]), |x, h| self.call(x, h))
+    }
+}
+
+impl Module for RNNCell {
+    fn zero_grad(&mut self) {
+        self.layer.zero_grad()
+    }
+
+    fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
+        self.layer.parameters()
+    }
+
+    fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
+        self.layer.parameters_mut()
+    }
+
+    fn named_parameters(&self) -> Box<dyn Iterator<Item = (String, &Value)> + '_> {
+        Box::new(prefixed("layer".to_string(), &self.layer))
+    }
+}
+
+impl Forward for RNNCell {
+    /// Treats input as a sequence of inputs of `input_size` flattened one after another and returns
+    /// the last hidden state starting from zeros.
+    fn forward(&self, x: &[Value]) -> Vec<Value> {
+        let xs = x.chunks(self.input_size.max(1)).map(|x| x.to_vec()).collect::<Vec<_>>();
+        self.unroll(xs.as_slice(), None).pop().unwrap_or_else(|| vec![Value::constant(0.); self.hidden_size()])
+    }
+}
+
+impl Display for RNNCell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("RNNCell({}, {})", self.input_size, self.hidden_size()))
+    }
+}
+
+/// Gated recurrent unit cell: update gate `z` and reset gate `r` control how the hidden state changes,
+/// `h' = (1 - z) * n + z * h`, where `n = tanh(W_in * x + b_in + r * (W_hn * h + b_hn))`.
+#[derive(Clone, Debug)]
+pub struct GRUCell {
+    update: Layer,
+    reset: Layer,
+    candidate_input: Layer,
+    candidate_hidden: Layer,
+    input_size: usize,
+}
+
+impl GRUCell {
+    pub fn new(input_size: usize, hidden_size: usize) -> Self {
+        Self::new_with_rng(input_size, hidden_size, &mut rand::thread_rng())
+    }
+
+    /// Creates cell using given random number generator. Weights are drawn from `U(-k, k)`, where
+    /// `k = 1 / sqrt(hidden_size)`, as it is common for recurrent cells.
+    pub fn new_with_rng(input_size: usize, hidden_size: usize, rng: &mut (impl Rng + ?Sized)) -> Self {
+        let k = 1. / (hidden_size.max(1) as Float).sqrt();
+        let init = Init::Uniform { low: -k, high: k };
+        let mut layer = |nin: usize, ntype: NeuronType| Layer::new_with_init(nin, hidden_size, ntype, init, rng);
+
+        Self {
+            update: layer(input_size + hidden_size, NeuronType::Sigmoid),
+            reset: layer(input_size + hidden_size, NeuronType::Sigmoid),
+            candidate_input: layer(input_size, NeuronType::Linear),
+            candidate_hidden: layer(hidden_size, NeuronType::Linear),
+            input_size,
+        }
+    }
+
+    /// Returns size of the hidden state.
+    pub fn hidden_size(&self) -> usize {
+        self.update.neurons.len()
+    }
+
+    /// Computes next hidden state from given input and previous hidden state.
+    pub fn call(&self, x: &[Value], h: &[Value]) -> Vec<Value> {
+        assert_eq!(x.len(), self.input_size, "input size does not match configuration");
+        assert_eq!(h.len(), self.hidden_size(), "hidden state size does not match configuration");
+
+        let xh = x.iter().chain(h.iter()).cloned().collect::<Vec<_>>();
+        let (z, r) = (self.update.call(xh.as_slice()), self.reset.call(xh.as_slice()));
+        let (nx, nh) = (self.candidate_input.call(x), self.candidate_hidden.call(h));
+
+        (0..h.len())
+            .map(|idx| {
+                let n = (&nx[idx] + &r[idx] * &nh[idx]).tanh();
+                (1. - &z[idx]) * n + &z[idx] * &h[idx]
+            })
+            .collect()
+    }
+
+    /// Applies the cell to a sequence of inputs starting from given hidden state (zeros if not specified)
+    /// and returns all hidden states.
+    pub fn unroll(&self, xs: &[Vec<Value>], h0: Option<Vec<Value>>) -> Vec<Vec<Value>> {
+        unroll(xs, h0.unwrap_or_else(|| vec![Value::constant(0.); self.hidden_size()]), |x, h| self.call(x, h))
+    }
+
+    fn layers(&self) -> [&Layer; 4] {
+        [&self.update, &self.reset, &self.candidate_input, &self.candidate_hidden]
+    }
+}
+
+impl Module for GRUCell {
+    fn zero_grad(&mut self) {
+        self.parameters_mut().for_each(|p| p.zero_grad())
+    }
+
+    fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
+        Box::new(unique(self.layers().into_iter().flat_map(|layer| layer.parameters()), |p| p.id()))
+    }
+
+    fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
+        let layers = [&mut self.update, &mut self.reset, &mut self.candidate_input, &mut self.candidate_hidden];
+        Box::new(unique(layers.into_iter().flat_map(|layer| layer.parameters_mut()), |p| p.id()))
+    }
+
+    fn named_parameters(&self) -> Box<dyn Iterator<Item = (String, &Value)> + '_> {
+        let names = ["update", "reset", "candidate_input", "candidate_hidden"];
+        let parameters =
+            names.into_iter().zip(self.layers()).flat_map(|(name, layer)| prefixed(name.to_string(), layer));
+        Box::new(unique(parameters, |(_, p)| p.id()))
+    }
+}
+
+impl Forward for GRUCell {
+    /// Treats input as a sequence of inputs of `input_size` flattened one after another and returns
+    /// the last hidden state starting from zeros.
+    fn forward(&self, x: &[Value]) -> Vec<Value> {
+        let xs = x.chunks(self.input_size.max(1)).map(|x| x.to_vec()).collect::<Vec<_>>();
+        self.unroll(xs.as_slice(), None).pop().unwrap_or_else(|| vec![Value::constant(0.); self.hidden_size()])
+    }
+}
+
+impl Display for GRUCell {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("GRUCell({}, {})", self.input_size, self.hidden_size()))
+    }
+}
+
+/// A lookup table which maps indices to rows of learnable values: the rows are returned as they are,
+/// so gradients accumulate only into the looked up rows.
+#[derive(Debug)]
+pub struct Embedding {
+    weights: Vec<Vec<Value>>,
+}
+
+impl Embedding {
+    /// Creates embedding with rows drawn from standard normal distribution.
+    pub fn new(num_embeddings: usize, dim: usize) -> Self {
+        Self::new_with_rng(num_embeddings, dim, &mut rand::thread_rng())
+    }
+
+    /// Creates embedding using given random number generator.
+    pub fn new_with_rng(num_embeddings: usize, dim: usize, rng: &mut (impl Rng + ?Sized)) -> Self {
+        let init = Init::Normal { mean: 0., std: 1. };
+        Self { weights: (0..num_embeddings).map(|_| init_weights(dim, 1, init, rng)).collect() }
+    }
+
+    /// Returns embedding which rows are reinitialized using given strategy.
+    pub fn with_init(self, init: Init) -> Self {
+        self.with_init_rng(init, &mut rand::thread_rng())
+    }
+
+    /// Same as `with_init`, but uses given random number generator.
+    pub fn with_init_rng(self, init: Init, rng: &mut (impl Rng + ?Sized)) -> Self {
+        let dim = self.dim();
+        Self { weights: self.weights.iter().map(|_| init_weights(dim, 1, init, rng)).collect() }
+    }
+
+    /// Returns size of each row.
+    pub fn dim(&self) -> usize {
+        self.weights.first().map_or(0, |row| row.len())
+    }
+
+    /// Returns a row for given index.
+    pub fn lookup(&self, index: usize) -> &[Value] {
+        self.weights.get(index).map(|row| row.as_slice()).expect("index is out of range of embedding")
+    }
+
+    pub fn call(&self, indices: &[usize]) -> Vec<Vec<Value>> {
+        indices.iter().map(|&index| self.lookup(index).to_vec()).collect()
+    }
+}
+
+impl Module for Embedding {
+    fn zero_grad(&mut self) {
+        self.parameters_mut().for_each(|p| p.zero_grad())
+    }
+
+    fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
+        Box::new(self.weights.iter().flatten())
+    }
+
+    fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
+        Box::new(self.weights.iter_mut().flatten())
+    }
+
+    fn named_parameters(&self) -> Box<dyn Iterator<Item = (String, &Value)> + '_> {
+        Box::new(self.weights.iter().enumerate().flat_map(|(row, weights)| {
+            weights.iter().enumerate().map(move |(col, w)| (format!("weights.{row}.{col}"), w))
+        }))
+    }
+}
+
+/// Weights are copied into new storage, so the copy can be trained independently.
+impl Clone for Embedding {
+    fn clone(&self) -> Self {
+        Self { weights: self.weights.iter().map(|row| row.iter().map(Value::deep_clone).collect()).collect() }
+    }
+}
+
+impl Forward for Embedding {
+    /// Treats data of input values as indices and returns looked up rows concatenated.
+    fn forward(&self, x: &[Value]) -> Vec<Value> {
+        x.iter().flat_map(|index| self.lookup(index.get_data().round() as usize).iter().cloned()).collect()
+    }
+}
+
+impl Display for Embedding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Embedding({}, {})", self.weights.len(), self.dim()))
+    }
+}
+
+/// A container which applies modules one after another.
+#[derive(Default)]
+pub struct Sequential {
+    modules: Vec<Box<dyn ForwardModule>>,
+}
+
+impl Sequential {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns container with given module added to the end.
+    pub fn with(mut self, module: impl ForwardModule + 'static) -> Self {
+        self.push(module);
+        self
+    }
+
+    /// Adds module to the end.
+    pub fn push(&mut self, module: impl ForwardModule + 'static) {
+        self.modules.push(Box::new(module));
+    }
+
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    pub fn call(&self, x: &[Value]) -> Vec<Value> {
+        self.modules.iter().fold(x.to_vec(), |acc, module| module.forward(acc.as_slice()))
+    }
+}
+
+impl Module for Sequential {
+    fn zero_grad(&mut self) {
+        self.modules.iter_mut().for_each(|module| module.zero_grad())
+    }
+
+    fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
+        Box::new(unique(self.modules.iter().flat_map(|module| module.parameters()), |p| p.id()))
+    }
+
+    fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
+        Box::new(unique(self.modules.iter_mut().flat_map(|module| module.parameters_mut()), |p| p.id()))
+    }
+
+    fn named_parameters(&self) -> Box<dyn Iterator<Item = (String, &Value)> + '_> {
+        let modules = self.modules.iter().enumerate();
+        let parameters = modules.flat_map(|(idx, module)| prefixed(format!("modules.{idx}"), module.as_ref()));
+        Box::new(unique(parameters, |(_, p)| p.id()))
+    }
+
+    fn param_groups(&self) -> Vec<ParamGroup> {
+        let groups = self.modules.iter().enumerate();
+        groups.map(|(idx, module)| ParamGroup::new(&format!("modules.{idx}"), module.parameters())).collect()
+    }
+
+    fn grad_report(&self) -> Vec<GradStats> {
+        self.modules.iter().map(|module| GradStats::new(module.parameters())).collect()
+    }
+
+    fn set_training(&mut self, is_training: bool) {
+        self.modules.iter_mut().for_each(|module| module.set_training(is_training))
+    }
+}
+
+impl Forward for Sequential {
+    fn forward(&self, x: &[Value]) -> Vec<Value> {
+        self.call(x)
+    }
+}
+
+impl Display for Sequential {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let modules = self.modules.iter().map(|module| module.to_string()).collect::<Vec<_>>().join(",");
+
+        f.write_fmt(format_args!("Sequential of [{}]", modules))
+    }
+}
+
+/// Skip connection block computing `f(x) + x`: when sizes of input and output of `f` differ, the input
+/// is mapped by a learnable linear projection without bias.
+pub struct Residual {
+    inner: Box<dyn ForwardModule>,
+    projection: Option<Layer>,
+}
+
+impl Residual {
+    /// Creates block around given module which maps `nin` values into `nout` ones.
+    pub fn new(inner: impl ForwardModule + 'static, nin: usize, nout: usize) -> Self {
+        Self::new_with_rng(inner, nin, nout, &mut rand::thread_rng())
+    }
+
+    /// Same as `new`, but uses given random number generator to create projection.
+    pub fn new_with_rng(
+        inner: impl ForwardModule + 'static,
+        nin: usize,
+        nout: usize,
+        rng: &mut (impl Rng + ?Sized),
+    ) -> Self {
+        let projection =
+            (nin != nout).then(|| Layer::new_with_rng(nin, nout, NeuronType::Linear, rng).with_bias(false));
+        Self { inner: Box::new(inner), projection }
+    }
+
+    pub fn call(&self, x: &[Value]) -> Vec<Value> {
+        let fx = self.inner.forward(x);
+        let skip = self.projection.as_ref().map_or_else(|| x.to_vec(), |projection| projection.call(x));
+        assert_eq!(fx.len(), skip.len(), "output size of residual block does not match configuration");
+
+        fx.iter().zip(skip.iter()).map(|(fx, skip)| fx + skip).collect()
+    }
+}
+
+impl Module for Residual {
+    fn zero_grad(&mut self) {
+        self.parameters_mut().for_each(|p| p.zero_grad())
+    }
+
+    fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
+        let projection = self.projection.iter().flat_map(|projection| projection.parameters());
+        Box::new(unique(self.inner.parameters().chain(projection), |p| p.id()))
+    }
+
+    fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
+        let projection = self.projection.iter_mut().flat_map(|projection| projection.parameters_mut());
+        Box::new(unique(self.inner.parameters_mut().chain(projection), |p| p.id()))
+    }
+
+    fn named_parameters(&self) -> Box<dyn Iterator<Item = (String, &Value)> + '_> {
+        let projection = self.projection.iter().flat_map(|projection| prefixed("projection".to_string(), projection));
+        Box::new(unique(prefixed("inner".to_string(), self.inner.as_ref()).chain(projection), |(_, p)| p.id()))
+    }
+
+    fn set_training(&mut self, is_training: bool) {
+        self.inner.set_training(is_training)
+    }
+}
+
+impl Forward for Residual {
+    fn forward(&self, x: &[Value]) -> Vec<Value> {
+        self.call(x)
+    }
+}
+
+impl Display for Residual {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.projection.as_ref() {
+            Some(projection) => {
+                f.write_fmt(format_args!("Residual of [{}] with projection {}", self.inner, projection))
+            }
+            None => f.write_fmt(format_args!("Residual of [{}]", self.inner)),
+        }
+    }
+}
+
+/// Single head scaled dot-product attention over a sequence of tokens: each output token is a sum of value
+/// projections weighted by `softmax(q_i * k_j / sqrt(d))`, where query, key and value are linear projections.
+#[derive(Clone, Debug)]
+pub struct Attention {
+    query: Layer,
+    key: Layer,
+    value: Layer,
+    is_causal: bool,
+}
+
+impl Attention {
+    /// Creates attention which projects tokens of `dim_in` size into queries, keys and values of `dim` size.
+    pub fn new(dim_in: usize, dim: usize) -> Self {
+        Self::new_with_rng(dim_in, dim, &mut rand::thread_rng())
+    }
+
+    /// Creates attention using given random number generator.
+    pub fn new_with_rng(dim_in: usize, dim: usize, rng: &mut (impl Rng + ?Sized)) -> Self {
+        let mut linear = || Layer::new_with_init(dim_in, dim, NeuronType::Linear, Init::Xavier, rng);
+        Self { query: linear(), key: linear(), value: linear(), is_causal: false }
+    }
+
+    /// Returns attention where a token attends only to itself and preceding tokens.
+    pub fn with_causal_mask(self, is_causal: bool) -> Self {
+        Self { is_causal, ..self }
+    }
+
+    /// Returns size of input tokens.
+    pub fn dim_in(&self) -> usize {
+        self.query.neurons.first().map_or(0, |neuron| neuron.w.len())
+    }
+
+    pub fn call(&self, tokens: &[Vec<Value>]) -> Vec<Vec<Value>> {
+        let project = |layer: &Layer| tokens.iter().map(|token| layer.call(token.as_slice())).collect::<Vec<_>>();
+        let (queries, keys, values) = (project(&self.query), project(&self.key), project(&self.value));
+        let scale = (self.query.neurons.len().max(1) as Float).sqrt();
+
+        queries
+            .iter()
+            .enumerate()
+            .map(|(idx, query)| {
+                let visible = if self.is_causal { idx + 1 } else { keys.len() };
+                let scores = keys[..visible]
+                    .iter()
+                    .map(|key| super::functions::dot(query.as_slice(), key.as_slice()) / scale)
+                    .collect::<Vec<_>>();
+                let weights = super::functions::softmax(scores.as_slice());
+
+                (0..self.value.neurons.len())
+                    .map(|dim| {
+                        let weighted = weights.iter().zip(values.iter()).map(|(w, v)| w * &v[dim]).collect::<Vec<_>>();
+                        super::functions::sum(weighted.as_slice())
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Module for Attention {
+    fn zero_grad(&mut self) {
+        self.parameters_mut().for_each(|p| p.zero_grad())
+    }
+
+    fn parameters(&self) -> Box<dyn Iterator<Item = &Value> + '_> {
+        let parameters = [&self.query, &self.key, &self.value].into_iter().flat_map(|layer| layer.parameters());
+        Box::new(unique(parameters, |p| p.id()))
+    }
+
+    fn parameters_mut(&mut self) -> Box<dyn Iterator<Item = &mut Value> + '_> {
+        let layers = [&mut self.query, &mut self.key, &mut self.value];
+        Box::new(unique(layers.into_iter().flat_map(|layer| layer.parameters_mut()), |p| p.id()))
+    }
+
+    fn named_parameters(&self) -> Box<dyn Iterator<Item = (String, &Value)> + '_> {
+        let layers = [("query", &self.query), ("key", &self.key), ("value", &self.value)];
+        let parameters = layers.into_iter().flat_map(|(name, layer)| prefixed(name.to_string(), layer));
+        Box::new(unique(parameters, |(_, p)| p.id()))
+    }
+}
+
+impl Forward for Attention {
+    /// Treats input as a sequence of tokens of `dim_in` size flattened one after another.
+    fn forward(&self, x: &[Value]) -> Vec<Value> {
+        let tokens = x.chunks(self.dim_in().max(1)).map(|token| token.to_vec()).collect::<Vec<_>>();
+        self.call(tokens.as_slice()).concat()
+    }
+}
+
+impl Display for Attention {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Attention({}, {})", self.dim_in(), self.query.neurons.len()))
+    }
+}
+
+/// Neurons are serialized as their activation and data of parameters, custom activations are not supported.
+/// A parameter shared with a previously stored one (tied) is stored as an index of that parameter.
+#[cfg(feature = "serde")]
+mod serialization {
+    use super::*;
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::path::Path;
+
+    /// A header of the binary format followed by its version.
+    const MAGIC: &[u8; 4] = b"MGRD";
+    const VERSION: u8 = 2;
+    /// A type of parameter data in the binary format regardless of `Float` type.
+    type Stored = f64;
+
+    impl MLP {
+        /// Saves the model into the file: JSON is used when the file has `json` extension, otherwise
+        /// a compact binary format which stores data of parameters as little endian `f64`.
+        pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+            let path = path.as_ref();
+            let bytes = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json")) {
+                serde_json::to_vec(self).map_err(|err| err.to_string())?
+            } else {
+                self.to_bytes()?
+            };
+
+            std::fs::write(path, bytes).map_err(|err| format!("cannot write '{}': {err}", path.display()))
+        }
+
+        /// Loads the model saved by `save`, the format is detected from the file content.
+        pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+            let path = path.as_ref();
+            let bytes = std::fs::read(path).map_err(|err| format!("cannot read '{}': {err}", path.display()))?;
+
+            let mlp = if bytes.starts_with(MAGIC) {
+                Self::from_bytes(bytes.as_slice())?
+            } else {
+                serde_json::from_slice::<Self>(bytes.as_slice()).map_err(|err| err.to_string())?
+            };
+            mlp.validate()?;
+
+            Ok(mlp)
+        }
+
+        /// Loads weights saved by `save` into the model with the same architecture.
+        pub fn load_weights(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
+            let other = Self::load(path)?;
+            if self.to_string() != other.to_string() {
+                return Err(format!("architecture mismatch: expected {self}, got {other}"));
+            }
+
+            self.load_state_dict(other.state_dict().as_slice())
+        }
+
+        /// Imports weights from JSON list of arrays dumped from a reference model, e.g. from PyTorch with
+        /// `json.dump([p.tolist() for p in model.parameters()], file)`: a weight matrix of `[nout, nin]` shape
+        /// followed by a bias vector of `[nout]` shape (if neurons have bias) for every layer. Like in PyTorch,
+        /// layers which share all parameters with a previous one (tied) have no arrays.
+        pub fn import_weights(&mut self, path: impl AsRef<Path>) -> Result<(), String> {
+            let path = path.as_ref();
+            let json =
+                std::fs::read_to_string(path).map_err(|err| format!("cannot read '{}': {err}", path.display()))?;
+            let arrays = serde_json::from_str::<Vec<Array>>(json.as_str()).map_err(|err| err.to_string())?;
+
+            let mut arrays = arrays.into_iter();
+            let mut data = Vec::with_capacity(self.parameters().count());
+            let mut seen = HashSet::new();
+            for (idx, layer) in self.layers.iter().enumerate() {
+                let ids = layer.parameters().map(|p| p.id()).collect::<Vec<_>>();
+                if !ids.is_empty() && ids.iter().all(|id| seen.contains(id)) {
+                    continue;
+                }
+                seen.extend(ids);
+
+                let (nout, nin) = (layer.neurons.len(), layer.neurons.first().map_or(0, |neuron| neuron.w.len()));
+                let has_bias = layer.neurons.iter().any(|neuron| neuron.b.is_some());
+
+                let weights = match arrays.next() {
+                    Some(Array::Matrix(weights))
+                        if weights.len() == nout && weights.iter().all(|row| row.len() == nin) =>
+                    {
+                        weights
+                    }
+                    _ => return Err(format!("layer {idx} expects weight matrix of [{nout}, {nin}] shape")),
+                };
+                let biases = if has_bias {
+                    match arrays.next() {
+                        Some(Array::Vector(biases)) if biases.len() == nout => biases,
+                        _ => return Err(format!("layer {idx} expects bias vector of [{nout}] shape")),
+                    }
+                } else {
+                    vec![0.; nout]
+                };
+
+                layer.neurons.iter().zip(weights.into_iter().zip(biases)).for_each(|(neuron, (weights, bias))| {
+                    data.extend(weights);
+                    data.extend(neuron.b.iter().map(|_| bias));
+                });
+            }
+
+            if arrays.next().is_some() {
+                return Err("there are more arrays than layers of the model".to_string());
+            }
+
+            // partially tied layers would repeat shared values, so they cannot be matched to unique parameters
+            let count = self.parameters().count();
+            if data.len() != count {
+                return Err(format!("expected {count} values of unique parameters, got {}", data.len()));
+            }
+
+            self.parameters_mut().zip(data).for_each(|(p, data)| p.set_data(data));
+
+            Ok(())
+        }
+
+        /// Checks that number of inputs of each layer matches number of outputs of the previous one.
+        fn validate(&self) -> Result<(), String> {
+            self.layers.iter().enumerate().try_fold(None, |nin: Option<usize>, (idx, layer)| {
+                let sizes = layer.neurons.iter().map(|neuron| neuron.w.len()).collect::<Vec<_>>();
+                match sizes.first() {
+                    None => Err(format!("layer {idx} has no neurons")),
+                    Some(&size) if sizes.iter().any(|&other| other != size) || nin.is_some_and(|nin| nin != size) => {
+                        Err(format!("layer {idx} has unexpected number of inputs"))
+                    }
+                    Some(_) => Ok(Some(sizes.len())),
+                }
+            })?;
+
+            Ok(())
+        }
+
+        fn to_bytes(&self) -> Result<Vec<u8>, String> {
+            let mut bytes = MAGIC.to_vec();
+            bytes.push(VERSION);
+            bytes.extend((self.layers.len() as u32).to_le_bytes());
+
+            let mut encoder = Encoder::default();
+            for layer in self.layers.iter() {
+                bytes.extend((layer.neurons.len() as u32).to_le_bytes());
+                for neuron in layer.neurons.iter() {
+                    bytes.push(match neuron.ntype {
+                        NeuronType::Linear => 0,
+                        NeuronType::ReLU => 1,
+                        NeuronType::Tanh => 2,
+                        NeuronType::Sigmoid => 3,
+                        NeuronType::Custom(_) => return Err("custom activation cannot be serialized".to_string()),
+                    });
+                    bytes.push(neuron.b.is_some() as u8);
+                    bytes.extend((neuron.w.len() as u32).to_le_bytes());
+                    neuron.parameters().for_each(|p| match encoder.encode(p) {
+                        Parameter::Data(data) => {
+                            bytes.push(0);
+                            bytes.extend((data as Stored).to_le_bytes());
+                        }
+                        Parameter::Tied { tied } => {
+                            bytes.push(1);
+                            bytes.extend((tied as u32).to_le_bytes());
+                        }
+                    });
+                }
+            }
+
+            Ok(bytes)
+        }
+
+        fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+            let mut reader = ByteReader { bytes: &bytes[MAGIC.len()..], version: 0, decoder: Decoder::default() };
+            reader.version = match reader.read::<1>()? {
+                [version @ 1..=VERSION] => version,
+                [version] => return Err(format!("unsupported version of binary format: {version}")),
+            };
+
+            let layers = (0..reader.read_u32()?)
+                .map(|_| {
+                    let neurons = (0..reader.read_u32()?)
+                        .map(|_| {
+                            let ntype = match reader.read::<1>()? {
+                                [0] => NeuronType::Linear,
+                                [1] => NeuronType::ReLU,
+                                [2] => NeuronType::Tanh,
+                                [3] => NeuronType::Sigmoid,
+                                [other] => return Err(format!("unknown activation: {other}")),
+                            };
+                            let has_bias = reader.read::<1>()? != [0];
+                            let w =
+                                (0..reader.read_u32()?).map(|_| reader.read_parameter()).collect::<Result<_, _>>()?;
+                            let b = if has_bias { Some(reader.read_parameter()?) } else { None };
+
+                            Ok(Neuron { w, b, ntype })
+                        })
+                        .collect::<Result<_, String>>()?;
+
+                    Ok(Layer { neurons })
+                })
+                .collect::<Result<_, String>>()?;
+
+            if !reader.bytes.is_empty() {
+                return Err("unexpected data at the end of binary format".to_string());
+            }
+
+            Ok(Self { layers })
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Array {
+        Vector(Vec<Float>),
+        Matrix(Vec<Vec<Float>>),
+    }
+
+    struct ByteReader<'a> {
+        bytes: &'a [u8],
+        version: u8,
+        decoder: Decoder,
+    }
+
+    impl ByteReader<'_> {
+        fn read<const N: usize>(&mut self) -> Result<[u8; N], String> {
+            if self.bytes.len() < N {
+                return Err("unexpected end of binary format".to_string());
+            }
+
+            let (head, tail) = self.bytes.split_at(N);
+            self.bytes = tail;
+
+            Ok(head.try_into().expect("slice has expected length"))
+        }
+
+        fn read_u32(&mut self) -> Result<u32, String> {
+            self.read().map(u32::from_le_bytes)
+        }
+
+        fn read_data(&mut self) -> Result<Float, String> {
+            self.read().map(|bytes| Stored::from_le_bytes(bytes) as Float)
+        }
+
+        /// Reads a parameter: the first version of the format stores data only, without a kind.
+        fn read_parameter(&mut self) -> Result<Value, String> {
+            let parameter = match self.version {
+                1 => Parameter::Data(self.read_data()?),
+                _ => match self.read::<1>()? {
+                    [0] => Parameter::Data(self.read_data()?),
+                    [1] => Parameter::Tied { tied: self.read_u32()? as usize },
+                    [other] => return Err(format!("unknown kind of parameter: {other}")),
+                },
+            };
+
+            self.decoder.decode(parameter)
+        }
+    }
+
+    /// Data of a parameter or an index of a previously stored parameter shared with it (tied).
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Parameter {
+        Data(Float),
+        Tied { tied: usize },
+    }
+
+    /// Assigns indices to parameters in order of appearance, so a shared parameter is stored only once.
+    #[derive(Default)]
+    struct Encoder {
+        indices: IdMap<usize>,
+    }
+
+    impl Encoder {
+        fn encode(&mut self, parameter: &Value) -> Parameter {
+            match self.indices.get(parameter) {
+                Some(&tied) => Parameter::Tied { tied },
+                None => {
+                    self.indices.insert(parameter, self.indices.len());
+                    Parameter::Data(parameter.get_data())
+                }
+            }
+        }
+    }
+
+    /// Restores parameters stored by `Encoder`, so tied parameters are shared again.
+    #[derive(Default)]
+    struct Decoder {
+        parameters: Vec<Value>,
+    }
+
+    impl Decoder {
+        fn decode(&mut self, parameter: Parameter) -> Result<Value, String> {
+            match parameter {
+                Parameter::Data(data) => {
+                    let parameter = Value::new(data);
+                    self.parameters.push(parameter.clone());
+                    Ok(parameter)
+                }
+                Parameter::Tied { tied } => {
+                    self.parameters.get(tied).cloned().ok_or_else(|| format!("unknown tied parameter: {tied}"))
+                }
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    enum Activation {
+        Linear,
+        ReLU,
+        Tanh,
+        Sigmoid,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "Neuron")]
+    struct NeuronData {
+        ntype: Activation,
+        w: Vec<Parameter>,
+        b: Option<Parameter>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "Layer")]
+    struct LayerData {
+        neurons: Vec<NeuronData>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "MLP")]
+    struct MLPData {
+        layers: Vec<LayerData>,
+    }
+
+    /// Converts a module into its serializable representation and back.
+    trait ModuleData: Sized {
+        type Module;
+
+        fn encode(module: &Self::Module, encoder: &mut Encoder) -> Result<Self, String>;
+
+        fn decode(self, decoder: &mut Decoder) -> Result<Self::Module, String>;
+    }
+
+    impl ModuleData for NeuronData {
+        type Module = Neuron;
+
+        fn encode(neuron: &Neuron, encoder: &mut Encoder) -> Result<Self, String> {
+            let ntype = match neuron.ntype {
+                NeuronType::Linear => Activation::Linear,
+                NeuronType::ReLU => Activation::ReLU,
+                NeuronType::Tanh => Activation::Tanh,
+                NeuronType::Sigmoid => Activation::Sigmoid,
+                NeuronType::Custom(_) => return Err("custom activation cannot be serialized".to_string()),
+            };
+            let w = neuron.w.iter().map(|w| encoder.encode(w)).collect();
+
+            Ok(Self { ntype, w, b: neuron.b.as_ref().map(|b| encoder.encode(b)) })
+        }
+
+        fn decode(self, decoder: &mut Decoder) -> Result<Neuron, String> {
+            if self.w.is_empty() && self.b.is_none() {
+                return Err("neuron should have at least one parameter".to_string());
+            }
+
+            let ntype = match self.ntype {
+                Activation::Linear => NeuronType::Linear,
+                Activation::ReLU => NeuronType::ReLU,
+                Activation::Tanh => NeuronType::Tanh,
+                Activation::Sigmoid => NeuronType::Sigmoid,
+            };
+            let w = self.w.into_iter().map(|w| decoder.decode(w)).collect::<Result<_, _>>()?;
+            let b = self.b.map(|b| decoder.decode(b)).transpose()?;
+
+            Ok(Neuron { w, b, ntype })
+        }
+    }
+
+    impl ModuleData for LayerData {
+        type Module = Layer;
+
+        fn encode(layer: &Layer, encoder: &mut Encoder) -> Result<Self, String> {
+            let neurons = layer.neurons.iter().map(|neuron| NeuronData::encode(neuron, encoder));
+
+            Ok(Self { neurons: neurons.collect::<Result<_, _>>()? })
+        }
+
+        fn decode(self, decoder: &mut Decoder) -> Result<Layer, String> {
+            let neurons = self.neurons.into_iter().map(|neuron| neuron.decode(decoder));
+
+            Ok(Layer { neurons: neurons.collect::<Result<_, _>>()? })
+        }
+    }
+
+    impl ModuleData for MLPData {
+        type Module = MLP;
+
+        fn encode(mlp: &MLP, encoder: &mut Encoder) -> Result<Self, String> {
+            let layers = mlp.layers.iter().map(|layer| LayerData::encode(layer, encoder));
+
+            Ok(Self { layers: layers.collect::<Result<_, _>>()? })
+        }
+
+        fn decode(self, decoder: &mut Decoder) -> Result<MLP, String> {
+            let layers = self.layers.into_iter().map(|layer| layer.decode(decoder));
+
+            Ok(MLP { layers: layers.collect::<Result<_, _>>()? })
+        }
+    }
+
+    /// Implements serde traits for the module using its serializable representation.
+    macro_rules! serde_impl {
+        ($module:ty, $data:ty) => {
+            impl Serialize for $module {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    <$data>::encode(self, &mut Encoder::default()).map_err(S::Error::custom)?.serialize(serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $module {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    <$data>::deserialize(deserializer)?.decode(&mut Decoder::default()).map_err(D::Error::custom)
+                }
+            }
+        };
+    }
+
+    serde_impl!(Neuron, NeuronData);
+    serde_impl!(Layer, LayerData);
+    serde_impl!(MLP, MLPData);
+}
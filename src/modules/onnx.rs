@@ -0,0 +1,244 @@
+//! Exports MLP into ONNX format using a minimal subset of its protobuf schema.
+
+use super::*;
+use prost::Message;
+use std::path::Path;
+
+const IR_VERSION: i64 = 8;
+const OPSET_VERSION: i64 = 13;
+const FLOAT_TYPE: i32 = 1;
+const INT_ATTRIBUTE: i32 = 2;
+
+/// ONNX tensors are exported as single precision floats regardless of `Float` type.
+type Stored = f32;
+
+impl MLP {
+    /// Exports the model into ONNX format: each layer becomes `Gemm` node followed by activation node.
+    /// The graph has `input` of `[N, nin]` shape and `output` of `[N, nout]` shape.
+    pub fn to_onnx(&self) -> Result<Vec<u8>, String> {
+        let layer = self.layers.first().ok_or_else(|| "empty model cannot be exported".to_string())?;
+        // NOTE layers without neurons are rejected below
+        let nin = layer.neurons.first().map_or(0, |neuron| neuron.w.len());
+
+        let mut graph = GraphProto { name: "mlp".to_string(), ..Default::default() };
+        let mut previous = "input".to_string();
+
+        for (idx, layer) in self.layers.iter().enumerate() {
+            let op_type = activation_op(layer)?;
+            let (nout, nin) = match layer.neurons.first() {
+                Some(neuron) => (layer.neurons.len(), neuron.w.len()),
+                None => return Err(format!("layer {idx} has no neurons")),
+            };
+            let (weight, bias, gemm) =
+                (format!("layers.{idx}.weight"), format!("layers.{idx}.bias"), format!("layers.{idx}.gemm"));
+
+            let weights = layer.neurons.iter().flat_map(|neuron| neuron.w.iter()).map(|w| w.get_data() as Stored);
+            graph.initializer.push(TensorProto::new(weight.as_str(), vec![nout as i64, nin as i64], weights.collect()));
+
+            let mut inputs = vec![previous, weight];
+            if layer.neurons.iter().any(|neuron| neuron.b.is_some()) {
+                let biases =
+                    layer.neurons.iter().map(|neuron| neuron.b.as_ref().map_or(0., |b| b.get_data() as Stored));
+                graph.initializer.push(TensorProto::new(bias.as_str(), vec![nout as i64], biases.collect()));
+                inputs.push(bias);
+            }
+
+            let transpose = AttributeProto { name: "transB".to_string(), i: 1, r#type: INT_ATTRIBUTE };
+            graph.node.push(NodeProto::new(
+                format!("layers.{idx}.Gemm"),
+                "Gemm",
+                inputs,
+                gemm.clone(),
+                vec![transpose],
+            ));
+            previous = gemm;
+
+            if let Some(op_type) = op_type {
+                let output = format!("layers.{idx}.{}", op_type.to_lowercase());
+                graph.node.push(NodeProto::new(
+                    format!("layers.{idx}.{op_type}"),
+                    op_type,
+                    vec![previous],
+                    output.clone(),
+                    vec![],
+                ));
+                previous = output;
+            }
+        }
+
+        let nout = self.layers.last().map_or(0, |layer| layer.neurons.len());
+        graph.node.last_mut().expect("model has nodes").output = vec!["output".to_string()];
+        graph.input.push(ValueInfoProto::new("input", nin));
+        graph.output.push(ValueInfoProto::new("output", nout));
+
+        let model = ModelProto {
+            ir_version: IR_VERSION,
+            producer_name: "mikrograd".to_string(),
+            producer_version: env!("CARGO_PKG_VERSION").to_string(),
+            graph: Some(graph),
+            opset_import: vec![OperatorSetIdProto { domain: String::new(), version: OPSET_VERSION }],
+        };
+
+        Ok(model.encode_to_vec())
+    }
+
+    /// Exports the model into ONNX file, see `to_onnx`.
+    pub fn save_onnx(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let bytes = self.to_onnx()?;
+
+        std::fs::write(path, bytes).map_err(|err| format!("cannot write '{}': {err}", path.display()))
+    }
+}
+
+/// Returns ONNX operator of the layer's activation, all neurons of the layer should share it.
+fn activation_op(layer: &Layer) -> Result<Option<&'static str>, String> {
+    let ops = layer.neurons.iter().map(|neuron| match neuron.ntype {
+        NeuronType::Linear => Ok(None),
+        NeuronType::ReLU => Ok(Some("Relu")),
+        NeuronType::Tanh => Ok(Some("Tanh")),
+        NeuronType::Sigmoid => Ok(Some("Sigmoid")),
+        NeuronType::Custom(_) => Err("custom activation cannot be exported".to_string()),
+    });
+
+    ops.collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .try_fold(None, |result, op| match result {
+            Some(other) if other != op => Err("neurons of a layer should have the same activation".to_string()),
+            _ => Ok(Some(op)),
+        })
+        .map(Option::flatten)
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct ModelProto {
+    #[prost(int64, tag = "1")]
+    pub ir_version: i64,
+    #[prost(string, tag = "2")]
+    pub producer_name: String,
+    #[prost(string, tag = "3")]
+    pub producer_version: String,
+    #[prost(message, optional, tag = "7")]
+    pub graph: Option<GraphProto>,
+    #[prost(message, repeated, tag = "8")]
+    pub opset_import: Vec<OperatorSetIdProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct OperatorSetIdProto {
+    #[prost(string, tag = "1")]
+    pub domain: String,
+    #[prost(int64, tag = "2")]
+    pub version: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct GraphProto {
+    #[prost(message, repeated, tag = "1")]
+    pub node: Vec<NodeProto>,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(message, repeated, tag = "5")]
+    pub initializer: Vec<TensorProto>,
+    #[prost(message, repeated, tag = "11")]
+    pub input: Vec<ValueInfoProto>,
+    #[prost(message, repeated, tag = "12")]
+    pub output: Vec<ValueInfoProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct NodeProto {
+    #[prost(string, repeated, tag = "1")]
+    pub input: Vec<String>,
+    #[prost(string, repeated, tag = "2")]
+    pub output: Vec<String>,
+    #[prost(string, tag = "3")]
+    pub name: String,
+    #[prost(string, tag = "4")]
+    pub op_type: String,
+    #[prost(message, repeated, tag = "5")]
+    pub attribute: Vec<AttributeProto>,
+}
+
+impl NodeProto {
+    fn new(name: String, op_type: &str, input: Vec<String>, output: String, attribute: Vec<AttributeProto>) -> Self {
+        Self { input, output: vec![output], name, op_type: op_type.to_string(), attribute }
+    }
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct AttributeProto {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(int64, tag = "3")]
+    pub i: i64,
+    #[prost(int32, tag = "20")]
+    pub r#type: i32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct TensorProto {
+    #[prost(int64, repeated, tag = "1")]
+    pub dims: Vec<i64>,
+    #[prost(int32, tag = "2")]
+    pub data_type: i32,
+    #[prost(float, repeated, tag = "4")]
+    pub float_data: Vec<f32>,
+    #[prost(string, tag = "8")]
+    pub name: String,
+}
+
+impl TensorProto {
+    fn new(name: &str, dims: Vec<i64>, float_data: Vec<f32>) -> Self {
+        Self { dims, data_type: FLOAT_TYPE, float_data, name: name.to_string() }
+    }
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct ValueInfoProto {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(message, optional, tag = "2")]
+    pub r#type: Option<TypeProto>,
+}
+
+impl ValueInfoProto {
+    /// Creates description of float tensor with dynamic batch size and given number of features.
+    fn new(name: &str, features: usize) -> Self {
+        let dim = vec![
+            Dimension { dim_value: None, dim_param: Some("N".to_string()) },
+            Dimension { dim_value: Some(features as i64), dim_param: None },
+        ];
+        let tensor_type = TensorTypeProto { elem_type: FLOAT_TYPE, shape: Some(TensorShapeProto { dim }) };
+
+        Self { name: name.to_string(), r#type: Some(TypeProto { tensor_type: Some(tensor_type) }) }
+    }
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct TypeProto {
+    #[prost(message, optional, tag = "1")]
+    pub tensor_type: Option<TensorTypeProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct TensorTypeProto {
+    #[prost(int32, tag = "1")]
+    pub elem_type: i32,
+    #[prost(message, optional, tag = "2")]
+    pub shape: Option<TensorShapeProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct TensorShapeProto {
+    #[prost(message, repeated, tag = "1")]
+    pub dim: Vec<Dimension>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(super) struct Dimension {
+    #[prost(int64, optional, tag = "1")]
+    pub dim_value: Option<i64>,
+    #[prost(string, optional, tag = "2")]
+    pub dim_param: Option<String>,
+}
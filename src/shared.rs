@@ -0,0 +1,44 @@
+//! Shared ownership primitives used by the graph within one thread, see `sync` module for thread safe ones.
+
+use super::float::Float;
+use super::value::{SharedGradientData, Value};
+use std::cell::{Ref, RefCell, RefMut};
+
+pub(crate) use std::cell::OnceCell as Once;
+pub(crate) use std::rc::Rc as Shared;
+
+pub(crate) type BackwardFn = Shared<Box<dyn Fn()>>;
+pub(crate) type ForwardFn = Shared<dyn Fn(&[Float]) -> Float>;
+pub(crate) type GradientFn = Shared<dyn Fn(&[SharedGradientData], &SharedGradientData)>;
+pub(crate) type DerivativeFn = Shared<dyn Fn(&[Value], &Value) -> Vec<Value>>;
+pub(crate) type HookFn = Shared<dyn Fn(Float) -> Float>;
+pub(crate) type ActivationFn = Shared<dyn Fn(Value) -> Value>;
+
+/// Nodes of the graph cannot be processed in parallel.
+pub(crate) const IS_PARALLEL: bool = false;
+
+/// Marks types which can be stored in the graph.
+pub trait MaybeSync {}
+
+impl<T: ?Sized> MaybeSync for T {}
+
+pub(crate) struct Lock<T>(RefCell<T>);
+
+impl<T> Lock<T> {
+    pub fn new(value: T) -> Self {
+        Self(RefCell::new(value))
+    }
+
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.0.borrow()
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        self.0.borrow_mut()
+    }
+}
+
+/// Applies the function to independent items, sequentially as there is no parallelism here.
+pub(crate) fn for_each_parallel<T: MaybeSync>(items: &[T], func: impl Fn(&T) + MaybeSync) {
+    items.iter().for_each(func)
+}
@@ -0,0 +1,41 @@
+//! Single precision variant of the crate API: the same values and modules, but data and gradients
+//! are stored as `f32`, e.g. to save memory. Values of different precision cannot be mixed in one graph.
+
+// sources of the crate root are compiled once again with another floating point type
+#![allow(clippy::duplicate_mod)]
+
+mod float {
+    pub(crate) use std::f32::consts;
+
+    /// A floating point type of values.
+    pub type Float = f32;
+}
+pub use self::float::Float;
+
+#[path = "compiled.rs"]
+mod compiled;
+pub use self::compiled::CompiledGraph;
+
+#[path = "dual.rs"]
+mod dual;
+pub use self::dual::DualValue;
+
+#[path = "functions.rs"]
+mod functions;
+pub use self::functions::*;
+
+#[path = "graph.rs"]
+mod graph;
+pub use self::graph::{Graph, GraphNode, GraphStats};
+
+#[path = "modules.rs"]
+mod modules;
+pub use self::modules::*;
+
+#[path = "shared.rs"]
+mod shared;
+pub use self::shared::MaybeSync;
+
+#[path = "value.rs"]
+mod value;
+pub use self::value::{checkpoint, is_grad_enabled, jvp, new_value, no_grad, vjp, GradMode, IdMap, Op, Tape, Value};
@@ -0,0 +1,41 @@
+//! Thread safe variant of the crate API: the same values and modules, but the graph can be shared
+//! between threads and wide graphs are processed in parallel during backward. Closures stored in
+//! the graph (hooks, custom operations and activations) have to be `Send` and `Sync`.
+
+// sources of the crate root are compiled once again with thread safe primitives
+#![allow(clippy::duplicate_mod)]
+
+#[cfg(test)]
+#[path = "../tests/unit/sync_test.rs"]
+mod sync_test;
+
+#[path = "float.rs"]
+mod float;
+pub use self::float::Float;
+
+#[path = "compiled.rs"]
+mod compiled;
+pub use self::compiled::CompiledGraph;
+
+#[path = "dual.rs"]
+mod dual;
+pub use self::dual::DualValue;
+
+#[path = "functions.rs"]
+mod functions;
+pub use self::functions::*;
+
+#[path = "graph.rs"]
+mod graph;
+pub use self::graph::{Graph, GraphNode, GraphStats};
+
+#[path = "modules.rs"]
+mod modules;
+pub use self::modules::*;
+
+mod shared;
+pub use self::shared::MaybeSync;
+
+#[path = "value.rs"]
+mod value;
+pub use self::value::{checkpoint, is_grad_enabled, jvp, new_value, no_grad, vjp, GradMode, IdMap, Op, Tape, Value};
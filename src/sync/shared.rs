@@ -0,0 +1,45 @@
+//! Thread safe shared ownership primitives used by the graph of `sync` module.
+
+use super::float::Float;
+use super::value::{SharedGradientData, Value};
+use rayon::prelude::*;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+pub(crate) use std::sync::Arc as Shared;
+pub(crate) use std::sync::OnceLock as Once;
+
+pub(crate) type BackwardFn = Shared<Box<dyn Fn() + Send + Sync>>;
+pub(crate) type ForwardFn = Shared<dyn Fn(&[Float]) -> Float + Send + Sync>;
+pub(crate) type GradientFn = Shared<dyn Fn(&[SharedGradientData], &SharedGradientData) + Send + Sync>;
+pub(crate) type DerivativeFn = Shared<dyn Fn(&[Value], &Value) -> Vec<Value> + Send + Sync>;
+pub(crate) type HookFn = Shared<dyn Fn(Float) -> Float + Send + Sync>;
+pub(crate) type ActivationFn = Shared<dyn Fn(Value) -> Value + Send + Sync>;
+
+/// Independent nodes of wide graphs are processed in parallel during backward.
+pub(crate) const IS_PARALLEL: bool = true;
+
+/// Marks types which can be stored in the graph: they have to be `Send` and `Sync`.
+pub trait MaybeSync: Send + Sync {}
+
+impl<T: Send + Sync + ?Sized> MaybeSync for T {}
+
+pub(crate) struct Lock<T>(RwLock<T>);
+
+impl<T> Lock<T> {
+    pub fn new(value: T) -> Self {
+        Self(RwLock::new(value))
+    }
+
+    pub fn borrow(&self) -> RwLockReadGuard<'_, T> {
+        self.0.read().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn borrow_mut(&self) -> RwLockWriteGuard<'_, T> {
+        self.0.write().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Applies the function to independent items using rayon's thread pool.
+pub(crate) fn for_each_parallel<T: MaybeSync>(items: &[T], func: impl Fn(&T) + MaybeSync) {
+    items.par_iter().for_each(func)
+}
@@ -2,222 +2,2282 @@
 #[path = "../tests/unit/value_test.rs"]
 mod value_test;
 
+use super::float::{consts, Float};
+use super::shared::{self, BackwardFn, DerivativeFn, ForwardFn, GradientFn, HookFn, Lock, MaybeSync, Once, Shared};
 use auto_ops::{impl_op, impl_op_commutative};
-use std::cell::RefCell;
-use std::collections::HashSet;
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::{Hash, Hasher};
-use std::iter::Sum;
-use std::ops::{Add, Mul};
-use std::rc::Rc;
+use std::iter::{Product, Sum};
+use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::ops::{Add, Div, Mul, Sub};
 
-pub(crate) type SharedGradientData = Rc<RefCell<GradientData>>;
-type BackwardFn = Rc<Box<dyn Fn()>>;
+pub(crate) type SharedGradientData = Shared<Lock<GradientData>>;
 
 pub(crate) struct GradientData {
-    grad: f64,
-    data: f64,
+    pub(crate) grad: Float,
+    pub(crate) data: Float,
+    hooks: Vec<HookFn>,
+    label: Option<String>,
 }
 
 impl GradientData {
-    pub fn new_shared(data: f64) -> SharedGradientData {
-        Rc::new(RefCell::new(Self { grad: 0., data }))
+    pub fn new_shared(data: Float) -> SharedGradientData {
+        Shared::new(Lock::new(Self { grad: 0., data, hooks: Vec::default(), label: None }))
     }
 }
 
+thread_local! {
+    static IS_GRAD_ENABLED: Cell<bool> = const { Cell::new(true) };
+}
+
+pub fn new_value(data: impl Into<Float>) -> Value {
+    Value::new(data.into())
+}
+
+/// Runs given function without recording the computation graph: values created inside have no
+/// children and do not propagate gradients, which saves memory and time during inference.
+pub fn no_grad<T>(func: impl FnOnce() -> T) -> T {
+    with_grad_enabled(false, func)
+}
+
+/// Runs given function with recording of the computation graph enabled or disabled, restores previous state after.
+/// NOTE graphs rebuilt internally (e.g. by checkpoint during backward) are recorded even inside `no_grad`.
+fn with_grad_enabled<T>(is_enabled: bool, func: impl FnOnce() -> T) -> T {
+    struct RestoreGuard(bool);
+
+    impl Drop for RestoreGuard {
+        fn drop(&mut self) {
+            IS_GRAD_ENABLED.with(|enabled| enabled.set(self.0));
+        }
+    }
+
+    let _guard = RestoreGuard(IS_GRAD_ENABLED.with(|enabled| enabled.replace(is_enabled)));
+
+    func()
+}
+
+/// Checks whether operations on values are recorded into the computation graph.
+pub fn is_grad_enabled() -> bool {
+    IS_GRAD_ENABLED.with(|enabled| enabled.get())
+}
+
+/// Gives explicit control over which parts of a computation are differentiated: while the tape exists,
+/// only operations performed between `start` and `stop` (or inside `record`) are recorded into the graph,
+/// other operations are computed as pure scalar math. Previous recording state is restored on drop.
+pub struct Tape {
+    previous: bool,
+    // NOTE recording state is thread local, so the tape has to stay on the thread which created it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Tape {
+    /// Creates a tape which is not recording.
+    pub fn new() -> Self {
+        Self { previous: IS_GRAD_ENABLED.with(|enabled| enabled.replace(false)), _not_send: PhantomData }
+    }
+
+    /// Starts recording operations.
+    pub fn start(&self) {
+        IS_GRAD_ENABLED.with(|enabled| enabled.set(true));
+    }
+
+    /// Stops recording operations.
+    pub fn stop(&self) {
+        IS_GRAD_ENABLED.with(|enabled| enabled.set(false));
+    }
+
+    /// Checks whether the tape records operations.
+    pub fn is_recording(&self) -> bool {
+        is_grad_enabled()
+    }
+
+    /// Runs given function recording its operations, previous recording state is restored after.
+    pub fn record<T>(&self, func: impl FnOnce() -> T) -> T {
+        with_grad_enabled(true, func)
+    }
+}
+
+impl Default for Tape {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for Tape {
+    fn drop(&mut self) {
+        IS_GRAD_ENABLED.with(|enabled| enabled.set(self.previous));
+    }
+}
+
+/// Evaluates given function on inputs without keeping its intermediate nodes: the result is a single
+/// node of the graph and the function is recomputed during backward to get gradients. This trades
+/// compute for memory in deep computations. Values captured by the function (e.g. parameters) receive
+/// gradients as well, but they should be leaves of the graph.
+pub fn checkpoint(inputs: &[Value], func: impl Fn(&[Value]) -> Value + MaybeSync + 'static) -> Value {
+    let func = Shared::new(func);
+    let (forward_func, gradient_func, derivative_func) = (func.clone(), func.clone(), func);
+
+    Value::new_nary(
+        inputs,
+        Op::Checkpoint,
+        move |data| {
+            no_grad(|| {
+                forward_func(data.iter().map(|&data| Value::new(data)).collect::<Vec<_>>().as_slice()).get_data()
+            })
+        },
+        move |children_gd, out_gd| {
+            let leaves = children_gd.iter().map(|child_gd| Value::new(child_gd.borrow().data)).collect::<Vec<_>>();
+            // seed recomputed graph with the upstream gradient, so captured values get correct gradients too
+            let upstream = out_gd.borrow().grad;
+            with_grad_enabled(true, || gradient_func(leaves.as_slice()).backward_with(upstream));
+
+            children_gd
+                .iter()
+                .zip(leaves.iter())
+                .for_each(|(child_gd, leaf)| child_gd.borrow_mut().grad += leaf.get_grad());
+        },
+        move |children, _out| with_grad_enabled(true, || derivative_func(children).grad_graph(children)),
+    )
+}
+
+/// Computes vector-Jacobian product: accumulates `sum_i(cotangents[i] * d outputs[i] / d leaf)` into
+/// gradients of graph leaves. This generalizes `backward` to multiple outputs with arbitrary seeds.
+pub fn vjp(outputs: &[Value], cotangents: &[Float]) {
+    assert_eq!(outputs.len(), cotangents.len(), "vjp requires a cotangent per output");
+
+    outputs.iter().zip(cotangents).map(|(output, &cotangent)| output * cotangent).sum::<Value>().backward()
+}
+
+/// Computes Jacobian-vector product: returns directional derivatives of outputs when inputs are moved
+/// along given tangents. Tangents are propagated forward through the existing graph, stored gradients
+/// are not changed.
+pub fn jvp(inputs: &[Value], tangents: &[Float], outputs: &[Value]) -> Vec<Float> {
+    assert_eq!(inputs.len(), tangents.len(), "jvp requires a tangent per input");
+
+    let mut dual: HashMap<_, Float> = HashMap::new();
+    inputs.iter().zip(tangents).for_each(|(input, &tangent)| *dual.entry(input.node_ptr()).or_default() += tangent);
+    let seeds = dual.keys().copied().collect::<HashSet<_>>();
+    let mut visited = HashSet::new();
+
+    outputs.iter().flat_map(|output| output.topological_order()).for_each(|v| {
+        if !visited.insert(v.node_ptr()) || seeds.contains(&v.node_ptr()) {
+            return;
+        }
+        let Some(derivative_fn) = v.derivative_fn.as_ref() else { return };
+
+        // NOTE derivatives are built with recording enabled as some of them (e.g. checkpoint) rely on the graph
+        let derivatives = with_grad_enabled(true, || derivative_fn(v.children.as_slice(), v));
+        let tangent = v
+            .children
+            .iter()
+            .zip(derivatives.iter())
+            .filter_map(|(child, derivative)| {
+                dual.get(&child.node_ptr()).map(|tangent| tangent * derivative.get_data())
+            })
+            .sum::<Float>();
+
+        dual.insert(v.node_ptr(), tangent);
+    });
+
+    outputs.iter().map(|output| dual.get(&output.node_ptr()).copied().unwrap_or_default()).collect()
+}
+
+/// An operation which produced a value: a leaf for values created directly.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Op {
+    Leaf,
+    Constant,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Neg,
+    Pow { exp: Float },
+    Powi { exp: i32 },
+    PowValue,
+    ReLU,
+    Tanh,
+    Sigmoid,
+    Exp,
+    Ln,
+    Log { base: Float },
+    Log2,
+    Log10,
+    Sqrt,
+    SqrtEps { eps: Float },
+    Abs,
+    Sin,
+    Cos,
+    Tan,
+    Atan,
+    Atan2,
+    Sinh,
+    Cosh,
+    Elu { alpha: Float },
+    SiLU,
+    Softplus,
+    Clamp { lo: Float, hi: Float },
+    Max,
+    Min,
+    Sign,
+    Floor,
+    Ceil,
+    Round,
+    Erf,
+    Recip,
+    Expm1,
+    Ln1p,
+    SignSte,
+    RoundSte,
+    SafeLn { eps: Float },
+    Softsign,
+    HardTanh,
+    HardSigmoid,
+    Logsumexp,
+    Fma,
+    Checkpoint,
+    Custom { name: String },
+}
+
+impl Op {
+    /// Applies operation to given children creating a new node of the graph. Returns `None` when there
+    /// are no children or the operation cannot be recreated (a leaf, a checkpoint or a custom operation which
+    /// function is unknown).
+    /// NOTE binary operations use the first and the last child, so the same operand can be passed once.
+    pub(crate) fn apply(&self, children: &[Value]) -> Option<Value> {
+        let (first, last) = (children.first()?, children.last()?);
+
+        Some(match self {
+            Op::Leaf | Op::Constant | Op::Checkpoint | Op::Custom { .. } => return None,
+            Op::Add => first + last,
+            Op::Sub => first - last,
+            Op::Mul => first * last,
+            Op::Div => first / last,
+            Op::PowValue => first.pow_value(last),
+            Op::Atan2 => first.atan2(last),
+            Op::Max => first.max(last),
+            Op::Min => first.min(last),
+            Op::Neg => -first,
+            Op::Pow { exp } => first.pow(*exp),
+            Op::Powi { exp } => first.powi(*exp),
+            Op::Log { base } => first.log(*base),
+            Op::SqrtEps { eps } => first.sqrt_eps(*eps),
+            Op::Elu { alpha } => first.elu(*alpha),
+            Op::Clamp { lo, hi } => first.clamp(*lo, *hi),
+            Op::SafeLn { eps } => first.safe_ln(*eps),
+            Op::ReLU => first.relu(),
+            Op::Tanh => first.tanh(),
+            Op::Sigmoid => first.sigmoid(),
+            Op::Exp => first.exp(),
+            Op::Ln => first.ln(),
+            Op::Log2 => first.log2(),
+            Op::Log10 => first.log10(),
+            Op::Sqrt => first.sqrt(),
+            Op::Abs => first.abs(),
+            Op::Sin => first.sin(),
+            Op::Cos => first.cos(),
+            Op::Tan => first.tan(),
+            Op::Atan => first.atan(),
+            Op::Sinh => first.sinh(),
+            Op::Cosh => first.cosh(),
+            Op::SiLU => first.silu(),
+            Op::Softplus => first.softplus(),
+            Op::Sign => first.sign(),
+            Op::Floor => first.floor(),
+            Op::Ceil => first.ceil(),
+            Op::Round => first.round(),
+            Op::Erf => first.erf(),
+            Op::Recip => first.recip(),
+            Op::Expm1 => first.expm1(),
+            Op::Ln1p => first.ln_1p(),
+            Op::SignSte => first.sign_ste(),
+            Op::RoundSte => first.round_ste(),
+            Op::Softsign => first.softsign(),
+            Op::HardTanh => first.hard_tanh(),
+            Op::HardSigmoid => first.hard_sigmoid(),
+            Op::Logsumexp => Value::logsumexp(children),
+            Op::Fma => Value::fma(first, children.get(1)?, last),
+        })
+    }
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Op::Leaf => "leaf",
+            Op::Constant => "constant",
+            Op::Add => "add",
+            Op::Sub => "sub",
+            Op::Mul => "mul",
+            Op::Div => "div",
+            Op::Neg => "neg",
+            Op::Pow { .. } => "pow",
+            Op::Powi { .. } => "powi",
+            Op::PowValue => "pow_value",
+            Op::ReLU => "relu",
+            Op::Tanh => "tanh",
+            Op::Sigmoid => "sigmoid",
+            Op::Exp => "exp",
+            Op::Ln => "ln",
+            Op::Log { .. } => "log",
+            Op::Log2 => "log2",
+            Op::Log10 => "log10",
+            Op::Sqrt => "sqrt",
+            Op::SqrtEps { .. } => "sqrt_eps",
+            Op::Abs => "abs",
+            Op::Sin => "sin",
+            Op::Cos => "cos",
+            Op::Tan => "tan",
+            Op::Atan => "atan",
+            Op::Atan2 => "atan2",
+            Op::Sinh => "sinh",
+            Op::Cosh => "cosh",
+            Op::Elu { .. } => "elu",
+            Op::SiLU => "silu",
+            Op::Softplus => "softplus",
+            Op::Clamp { .. } => "clamp",
+            Op::Max => "max",
+            Op::Min => "min",
+            Op::Sign => "sign",
+            Op::Floor => "floor",
+            Op::Ceil => "ceil",
+            Op::Round => "round",
+            Op::Erf => "erf",
+            Op::Recip => "recip",
+            Op::Expm1 => "expm1",
+            Op::Ln1p => "ln_1p",
+            Op::SignSte => "sign_ste",
+            Op::RoundSte => "round_ste",
+            Op::SafeLn { .. } => "safe_ln",
+            Op::Softsign => "softsign",
+            Op::HardTanh => "hard_tanh",
+            Op::HardSigmoid => "hard_sigmoid",
+            Op::Logsumexp => "logsumexp",
+            Op::Fma => "fma",
+            Op::Checkpoint => "checkpoint",
+            Op::Custom { name } => name.as_str(),
+        })
+    }
+}
+
+/// Specifies how backward treats gradients of leaves left from previous calls.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradMode {
+    /// Gradients are added to existing ones, e.g. to sum gradients of several losses.
+    Accumulate,
+    /// Gradients replace existing ones, so repeated backward calls produce the same result.
+    Overwrite,
+}
+
+/// A node of the computation graph. Cloning is cheap: clones refer to the same node, so they share
+/// data and gradient. Equality and hashing are defined by node identity (see `Value::id`), not by data.
 #[derive(Clone)]
 pub struct Value {
     grad_data: SharedGradientData,
-    children: Vec<Value>,
+    pub(crate) children: Shared<Vec<Value>>,
     backward_fn: Option<BackwardFn>,
-    op: String,
+    pub(crate) forward_fn: Option<ForwardFn>,
+    // NOTE unlike backward, it is not bound to the graph, so it can be applied to other gradient data
+    pub(crate) gradient_fn: Option<GradientFn>,
+    derivative_fn: Option<DerivativeFn>,
+    op: Op,
+    // NOTE children of a node never change, so its topological order can be computed once
+    topo_cache: Once<Shared<Vec<Value>>>,
+}
+
+impl Value {
+    pub(crate) fn new(data: Float) -> Self {
+        let grad_data = GradientData::new_shared(data);
+        Self {
+            grad_data,
+            children: Shared::default(),
+            backward_fn: None,
+            forward_fn: None,
+            gradient_fn: None,
+            derivative_fn: None,
+            op: Op::Leaf,
+            topo_cache: Once::default(),
+        }
+    }
+
+    /// Creates a leaf which holds a constant, e.g. a scalar operand: it can be folded by `optimize`.
+    pub(crate) fn constant(data: Float) -> Self {
+        let mut value = Value::new(data);
+        value.op = Op::Constant;
+        value
+    }
+
+    /// Creates a node which is not connected to the graph: used when gradient recording is disabled.
+    fn new_untracked(grad_data: SharedGradientData, op: Op) -> Self {
+        Self {
+            grad_data,
+            children: Shared::default(),
+            backward_fn: None,
+            forward_fn: None,
+            gradient_fn: None,
+            derivative_fn: None,
+            op,
+            topo_cache: Once::default(),
+        }
+    }
+
+    /// Returns underlying data.
+    pub fn get_data(&self) -> Float {
+        self.grad_data.borrow().data
+    }
+
+    /// Compares values by their data only using total ordering of `Float`, e.g. to sort values or to find max.
+    pub fn cmp_data(&self, other: &Value) -> Ordering {
+        self.get_data().total_cmp(&other.get_data())
+    }
+
+    pub fn set_data(&mut self, value: Float) {
+        self.grad_data.borrow_mut().data = value;
+    }
+
+    /// Returns this value with given label attached, e.g. `new_value(1.).with_label("x")`.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.set_label(label);
+        self
+    }
+
+    /// Sets a label used to identify the value when debugging: it is shared by all clones of the value.
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        self.grad_data.borrow_mut().label = Some(label.into());
+    }
+
+    /// Returns a label if it was set.
+    pub fn get_label(&self) -> Option<String> {
+        self.grad_data.borrow().label.clone()
+    }
+
+    /// Returns an operation which produced this value.
+    pub fn op(&self) -> &Op {
+        &self.op
+    }
+
+    /// Returns a gradient.
+    pub fn get_grad(&self) -> Float {
+        self.grad_data.borrow().grad
+    }
+
+    pub(crate) fn set_grad(&mut self, grad: Float) {
+        self.grad_data.borrow_mut().grad = grad;
+    }
+
+    /// Sets gradient to zero.
+    pub fn zero_grad(&mut self) {
+        self.grad_data.borrow_mut().grad = 0.;
+    }
+
+    /// Returns gradients of this value with respect to given values as new nodes of the graph, so they
+    /// can be differentiated again (e.g. to get second order derivatives or build gradient penalties).
+    /// Unlike `backward`, it does not change stored gradients. The gradient graph is recorded even inside `no_grad`.
+    pub fn grad_graph(&self, wrt: &[Value]) -> Vec<Value> {
+        with_grad_enabled(true, || self.build_grad_graph(wrt))
+    }
+
+    fn build_grad_graph(&self, wrt: &[Value]) -> Vec<Value> {
+        let mut adjoints: HashMap<_, Value> = HashMap::new();
+        adjoints.insert(self.node_ptr(), Value::constant(1.));
+
+        self.topological_order().iter().rev().for_each(|v| {
+            let Some(adjoint) = adjoints.get(&v.node_ptr()).cloned() else { return };
+            let Some(derivative_fn) = v.derivative_fn.as_ref() else { return };
+
+            v.children.iter().zip(derivative_fn(v.children.as_slice(), v)).for_each(|(child, derivative)| {
+                let contribution = &adjoint * derivative;
+                let accumulated = match adjoints.remove(&child.node_ptr()) {
+                    Some(accumulated) => accumulated + contribution,
+                    None => contribution,
+                };
+                adjoints.insert(child.node_ptr(), accumulated);
+            });
+        });
+
+        wrt.iter()
+            .map(|value| adjoints.get(&value.node_ptr()).cloned().unwrap_or_else(|| Value::constant(0.)))
+            .collect()
+    }
+
+    /// Returns a new leaf value with the same data, but without connection to the graph,
+    /// so it is treated as a constant by `backward`.
+    pub fn detach(&self) -> Value {
+        Value::new(self.get_data())
+    }
+
+    /// Applies gradients. Same as `backward_retain`: gradients of leaves are accumulated, while gradients
+    /// of intermediate nodes are reset to zero before they are computed.
+    pub fn backward(&self) {
+        self.backward_retain()
+    }
+
+    /// Applies gradients keeping the graph, so backward can be called again on it or on another
+    /// output sharing a part of the graph (e.g. `loss_a.backward_retain(); loss_b.backward_retain()`).
+    /// Gradients of leaves are accumulated across calls, while gradients of intermediate nodes are
+    /// recomputed from scratch on each call.
+    pub fn backward_retain(&self) {
+        self.backward_with_mode(GradMode::Accumulate)
+    }
+
+    /// Applies gradients keeping the graph using given mode for gradients of leaves.
+    pub fn backward_with_mode(&self, mode: GradMode) {
+        let topo = self.topological_order();
+
+        if mode == GradMode::Overwrite {
+            topo.iter().filter(|v| v.backward_fn.is_none()).for_each(|v| v.grad_data.borrow_mut().grad = 0.);
+        }
+
+        self.propagate(topo.as_slice(), 1., |_| true, |_| true)
+    }
+
+    /// Applies gradients using given gradient of this value instead of one, which is the same as backward
+    /// of `grad * self`, but without adding an extra node (e.g. to weight losses).
+    pub fn backward_with(&self, grad: Float) {
+        self.propagate(self.topological_order().as_slice(), grad, |_| true, |_| true)
+    }
+
+    /// Applies gradients only to given nodes: backward does not go past them and skips parts of the graph
+    /// which do not lead to them (e.g. frozen layers), so their gradients are left unchanged.
+    pub fn backward_to(&self, inputs: &[Value]) {
+        let topo = self.topological_order();
+        let targets = inputs.iter().map(|input| input.node_ptr()).collect::<HashSet<_>>();
+
+        // collect nodes which have at least one target as a descendant
+        let mut leads = HashSet::new();
+        topo.iter().for_each(|v| {
+            if v.children.iter().any(|child| targets.contains(&child.node_ptr()) || leads.contains(&child.node_ptr())) {
+                leads.insert(v.node_ptr());
+            }
+        });
+        let is_updated = |v: &Value| {
+            v.node_ptr() == self.node_ptr() || targets.contains(&v.node_ptr()) || leads.contains(&v.node_ptr())
+        };
+
+        // backward of a node changes all its children, so restore those which are not of interest
+        let unchanged = topo
+            .iter()
+            .filter(|v| leads.contains(&v.node_ptr()))
+            .flat_map(|v| v.children.iter())
+            .filter(|child| !is_updated(child))
+            .map(|child| (child, child.get_grad()))
+            .collect::<Vec<_>>();
+
+        self.propagate(topo.as_slice(), 1., is_updated, |v| leads.contains(&v.node_ptr()));
+
+        unchanged.into_iter().for_each(|(child, grad)| child.grad_data.borrow_mut().grad = grad);
+    }
+
+    fn propagate(
+        &self,
+        topo: &[&Value],
+        seed: Float,
+        is_updated: impl Fn(&Value) -> bool,
+        is_expanded: impl Fn(&Value) -> bool,
+    ) {
+        // reset gradients of intermediate nodes left from previous calls
+        topo.iter()
+            .filter(|v| v.backward_fn.is_some() && is_updated(v))
+            .for_each(|v| v.grad_data.borrow_mut().grad = 0.);
+
+        // hooks see only a gradient computed by this call, so keep what leaves have accumulated before
+        let previous = topo
+            .iter()
+            .filter(|v| !v.grad_data.borrow().hooks.is_empty() && is_updated(v))
+            .map(|v| (v.node_ptr(), if v.node_ptr() == self.node_ptr() { 0. } else { v.get_grad() }))
+            .collect::<HashMap<_, _>>();
+
+        let steps = topo.iter().rev().map(|v| (*v, previous.get(&v.node_ptr()).copied(), is_expanded(v)));
+
+        // go one variable at a time and apply the chain rule to get its gradient
+        self.grad_data.borrow_mut().grad = seed;
+        if shared::IS_PARALLEL {
+            parallel::backward(steps.collect());
+        } else {
+            steps.for_each(|(v, previous, is_expanded)| v.backward_step(previous, is_expanded));
+        }
+    }
+
+    fn backward_step(&self, previous: Option<Float>, is_expanded: bool) {
+        if let Some(previous) = previous {
+            self.apply_hooks(previous);
+        }
+        if let Some(backward) = self.backward_fn.as_ref().filter(|_| is_expanded) {
+            backward();
+        }
+    }
+
+    /// Registers a hook which is called with the gradient of this node once it is computed during
+    /// backward. The hook returns a gradient to be used instead (e.g. clipped), so it is propagated further.
+    pub fn register_hook(&self, hook: impl Fn(Float) -> Float + MaybeSync + 'static) {
+        self.grad_data.borrow_mut().hooks.push(Shared::new(hook));
+    }
+
+    /// Removes all hooks registered on this node.
+    pub fn clear_hooks(&self) {
+        self.grad_data.borrow_mut().hooks.clear();
+    }
+
+    fn apply_hooks(&self, previous: Float) {
+        let hooks = self.grad_data.borrow().hooks.clone();
+        let grad = hooks.iter().fold(self.get_grad() - previous, |grad, hook| hook(grad));
+        self.grad_data.borrow_mut().grad = previous + grad;
+    }
+
+    /// Applies gradients and releases the graph owned by this value: children and cached topological order
+    /// are dropped node by node, so even deep graphs are freed without recursion. Intermediate nodes which
+    /// are still referenced elsewhere keep their part of the graph.
+    pub fn backward_once(self) {
+        self.backward_retain();
+
+        let mut nodes = vec![self];
+        while let Some(mut node) = nodes.pop() {
+            node.topo_cache.take();
+            if let Ok(children) = Shared::try_unwrap(std::mem::take(&mut node.children)) {
+                nodes.extend(children);
+            }
+        }
+    }
+
+    /// Sets gradients of all intermediate (non-leaf) nodes of the graph to zero.
+    pub fn zero_intermediate_grads(&self) {
+        self.topological_order()
+            .iter()
+            .filter(|v| v.backward_fn.is_some())
+            .for_each(|v| v.grad_data.borrow_mut().grad = 0.);
+    }
+
+    /// Iterates over all nodes of the graph in topological order: children always precede their parents,
+    /// so this value is the last one.
+    pub fn iter_topo(&self) -> impl Iterator<Item = &Value> {
+        self.topological_order().into_iter()
+    }
+
+    /// Iterates over nodes of the graph which have no children (inputs, parameters and constants).
+    pub fn iter_leaves(&self) -> impl Iterator<Item = &Value> {
+        self.iter_topo().filter(|v| v.children.is_empty())
+    }
+
+    /// Renders the graph of this value in DOT format, e.g. to visualize it with Graphviz.
+    pub fn to_dot(&self) -> String {
+        super::Graph::from_value(self).to_dot()
+    }
+
+    /// Renders the graph of this value as a Mermaid flowchart, e.g. to embed it into markdown.
+    pub fn to_mermaid(&self) -> String {
+        super::Graph::from_value(self).to_mermaid()
+    }
+
+    /// Returns statistics of the graph built to compute this value: node count, depth, leaf count and
+    /// number of nodes per operation.
+    pub fn graph_stats(&self) -> super::GraphStats {
+        super::GraphStats::from_value(self)
+    }
+
+    /// Returns an equivalent graph where subtrees depending only on constants are folded into constants
+    /// and structurally identical nodes are merged. Leaves are shared with the original graph, so backward
+    /// of the result accumulates gradients into the same inputs and parameters.
+    pub fn optimize(&self) -> Value {
+        let mut rebuilt: HashMap<_, Value> = HashMap::new();
+        let mut unique: HashMap<(String, Vec<_>), Value> = HashMap::new();
+
+        self.topological_order().into_iter().for_each(|v| {
+            let children = v.children.iter().map(|child| rebuilt[&child.node_ptr()].clone()).collect::<Vec<_>>();
+
+            let value = match &v.op {
+                Op::Constant => unique
+                    .entry((format!("{:?}", v.get_data().to_bits()), Vec::default()))
+                    .or_insert_with(|| v.clone())
+                    .clone(),
+                Op::Checkpoint | Op::Custom { .. } => v.clone(),
+                _ if children.is_empty() => v.clone(),
+                _ if children.iter().all(|child| child.op == Op::Constant) => Value::constant(v.get_data()),
+                op => {
+                    let key = (format!("{op:?}"), children.iter().map(|child| child.node_ptr()).collect());
+                    unique
+                        .entry(key)
+                        .or_insert_with(|| op.apply(children.as_slice()).unwrap_or_else(|| v.clone()))
+                        .clone()
+                }
+            };
+
+            rebuilt.insert(v.node_ptr(), value);
+        });
+
+        rebuilt.remove(&self.node_ptr()).unwrap_or_else(|| self.clone())
+    }
+
+    /// Returns an equivalent graph where identity operations (`x * 1`, `x + 0`, `x.pow(1.)`, `-(-x)`, etc.)
+    /// are removed, chains of operations with constants are collapsed (e.g. `-(x * 2.) * 3.` becomes
+    /// `x * -6.`) and subtrees depending only on constants are folded. Unchanged nodes are shared with
+    /// the original graph.
+    pub fn simplify(&self) -> Value {
+        let mut rebuilt: HashMap<_, Value> = HashMap::new();
+
+        self.topological_order().into_iter().for_each(|v| {
+            let children = v.children.iter().map(|child| rebuilt[&child.node_ptr()].clone()).collect::<Vec<_>>();
+            let is_changed = children.iter().zip(v.children.iter()).any(|(new, old)| new != old);
+
+            let value = match Value::simplified(&v.op, children.as_slice()) {
+                _ if children.is_empty() => v.clone(),
+                _ if children.iter().all(|child| child.op == Op::Constant) => Value::constant(v.get_data()),
+                Some(value) => value,
+                None if is_changed => v.op.apply(children.as_slice()).unwrap_or_else(|| v.clone()),
+                None => v.clone(),
+            };
+
+            rebuilt.insert(v.node_ptr(), value);
+        });
+
+        rebuilt.remove(&self.node_ptr()).unwrap_or_else(|| self.clone())
+    }
+
+    /// Returns a simpler node equivalent to the operation applied to given children if there is one.
+    fn simplified(op: &Op, children: &[Value]) -> Option<Value> {
+        let is_constant = |value: &Value, data: Float| value.op == Op::Constant && value.get_data() == data;
+        let is_product = |op: &Op| matches!(op, Op::Mul | Op::Neg);
+        // splits children of a binary node into a non constant and a constant one
+        let split_constant = |children: &[Value]| match children {
+            [lhs, rhs] if lhs.op != Op::Constant && rhs.op == Op::Constant => Some((lhs.clone(), rhs.get_data())),
+            [lhs, rhs] if lhs.op == Op::Constant && rhs.op != Op::Constant => Some((rhs.clone(), lhs.get_data())),
+            _ => None,
+        };
+        let (first, last) = (children.first()?, children.last()?);
+
+        match op {
+            Op::Add if is_constant(first, 0.) => Some(last.clone()),
+            Op::Add | Op::Sub if is_constant(last, 0.) => Some(first.clone()),
+            Op::Mul if is_constant(first, 1.) => Some(last.clone()),
+            Op::Mul | Op::Div if is_constant(last, 1.) => Some(first.clone()),
+            Op::Pow { exp } if *exp == 1. => Some(first.clone()),
+            Op::Powi { exp: 1 } => Some(first.clone()),
+            Op::Add | Op::Mul | Op::Neg => {
+                let (inner, outer) = split_constant(children)?;
+                let (value, constant) = split_constant(inner.children.as_slice())
+                    .filter(|_| inner.op == *op || is_product(&inner.op) && is_product(op))?;
+
+                let collapsed = if *op == Op::Add { value + (constant + outer) } else { value * (constant * outer) };
+                Some(Value::simplified(&collapsed.op, collapsed.children.as_slice()).unwrap_or(collapsed))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns a copy of the whole graph with new storage for data and gradients, so backward passes on
+    /// the copy and the original do not interfere. Data, gradients, labels and hooks are copied.
+    /// Checkpoint and custom nodes are rebuilt over copied children using their stored functions.
+    /// NOTE values captured by a checkpoint function are not copied, so they still receive gradients.
+    pub fn deep_clone(&self) -> Value {
+        let mut copies: HashMap<_, Value> = HashMap::new();
+
+        self.topological_order().into_iter().for_each(|v| {
+            let children = v.children.iter().map(|child| copies[&child.node_ptr()].clone()).collect::<Vec<_>>();
+
+            let copy = match v.op {
+                Op::Checkpoint | Op::Custom { .. } => v.rebuild(children.as_slice()),
+                _ => v.op.apply(children.as_slice()),
+            };
+            let copy = match copy {
+                Some(copy) => copy.with_state_of(v),
+                None => {
+                    let mut copy = Value::new(v.get_data());
+                    copy.op = v.op.clone();
+                    copy.with_state_of(v)
+                }
+            };
+
+            copies.insert(v.node_ptr(), copy);
+        });
+
+        copies.remove(&self.node_ptr()).unwrap_or_else(|| self.clone())
+    }
+
+    /// Creates a node with the same operation and functions over given children. Returns `None`
+    /// if the node has no functions, e.g. it is a leaf or it was created without recording.
+    fn rebuild(&self, children: &[Value]) -> Option<Value> {
+        let forward_fn = self.forward_fn.clone()?;
+        let gradient_fn = self.gradient_fn.clone()?;
+        let derivative_fn = self.derivative_fn.clone()?;
+
+        Some(Value::new_nary(
+            children,
+            self.op.clone(),
+            move |data| forward_fn(data),
+            move |children_gd, out_gd| gradient_fn(children_gd, out_gd),
+            move |children, out| derivative_fn(children, out),
+        ))
+    }
+
+    fn with_state_of(self, other: &Value) -> Self {
+        {
+            let (mut target, source) = (self.grad_data.borrow_mut(), other.grad_data.borrow());
+            target.data = source.data;
+            target.grad = source.grad;
+            target.hooks = source.hooks.clone();
+            target.label = source.label.clone();
+        }
+        self
+    }
+
+    /// Compares graphs of two values by operations, data and topology instead of identity: shared nodes
+    /// must be shared in both graphs. Gradients and labels are ignored.
+    pub fn structurally_eq(&self, other: &Value) -> bool {
+        let (lhs, rhs) = (self.topological_order(), other.topological_order());
+        if lhs.len() != rhs.len() {
+            return false;
+        }
+
+        let index =
+            |topo: &[&Value]| topo.iter().enumerate().map(|(idx, v)| (v.node_ptr(), idx)).collect::<HashMap<_, _>>();
+        let (lhs_index, rhs_index) = (index(lhs.as_slice()), index(rhs.as_slice()));
+        let children =
+            |v: &Value, index: &HashMap<_, usize>| v.children.iter().map(|c| index[&c.node_ptr()]).collect::<Vec<_>>();
+
+        lhs.iter().zip(rhs.iter()).all(|(l, r)| {
+            let (l_data, r_data) = (l.get_data(), r.get_data());
+
+            l.op == r.op
+                && (l_data == r_data || l_data.is_nan() && r_data.is_nan())
+                && children(l, &lhs_index) == children(r, &rhs_index)
+        })
+    }
+
+    /// Returns estimated number of bytes held by the graph of this value: nodes, gradient cells, closures
+    /// and reference counting overhead. Shared nodes are counted once.
+    pub fn memory_footprint(&self) -> usize {
+        // strong and weak counters stored in every shared allocation
+        const SHARED_OVERHEAD: usize = 2 * std::mem::size_of::<usize>();
+
+        let node_size = |v: &Value| {
+            let grad_data = v.grad_data.borrow();
+            let grad_data_size = SHARED_OVERHEAD
+                + std::mem::size_of::<Lock<GradientData>>()
+                + grad_data.hooks.capacity() * std::mem::size_of::<HookFn>()
+                + grad_data
+                    .hooks
+                    .iter()
+                    .map(|hook| SHARED_OVERHEAD + std::mem::size_of_val(hook.as_ref()))
+                    .sum::<usize>()
+                + grad_data.label.as_ref().map_or(0, |label| label.capacity());
+            let children_size = SHARED_OVERHEAD
+                + std::mem::size_of::<Vec<Value>>()
+                + v.children.capacity() * std::mem::size_of::<Value>();
+            let closures_size =
+                v.backward_fn.as_ref().map_or(0, |f| {
+                    SHARED_OVERHEAD + std::mem::size_of::<Box<()>>() + std::mem::size_of_val(f.as_ref().as_ref())
+                }) + v.forward_fn.as_ref().map_or(0, |f| SHARED_OVERHEAD + std::mem::size_of_val(f.as_ref()))
+                    + v.gradient_fn.as_ref().map_or(0, |f| SHARED_OVERHEAD + std::mem::size_of_val(f.as_ref()))
+                    + v.derivative_fn.as_ref().map_or(0, |f| SHARED_OVERHEAD + std::mem::size_of_val(f.as_ref()));
+            let cache_size = v.topo_cache.get().map_or(0, |cache| {
+                SHARED_OVERHEAD + std::mem::size_of::<Vec<Value>>() + cache.capacity() * std::mem::size_of::<Value>()
+            });
+
+            grad_data_size + children_size + closures_size + cache_size
+        };
+
+        std::mem::size_of::<Value>() + self.topological_order().into_iter().map(node_size).sum::<usize>()
+    }
+
+    /// Iterates over direct children (operands) of this value.
+    pub fn iter_children(&self) -> impl Iterator<Item = &Value> {
+        self.children.iter()
+    }
+
+    /// Returns all nodes of the graph in topological order: children always precede their parents.
+    /// The order is cached on the first call, so repeated backward or forward passes on the same
+    /// output do not sort the graph again.
+    pub(crate) fn topological_order(&self) -> Vec<&Value> {
+        let descendants = self.topo_cache.get_or_init(|| {
+            let mut topo = self.sort_topologically();
+            topo.pop();
+            Shared::new(topo.into_iter().cloned().collect())
+        });
+
+        descendants.iter().chain(std::iter::once(self)).collect()
+    }
+
+    fn sort_topologically(&self) -> Vec<&Value> {
+        // use an explicit stack instead of recursion to support arbitrarily deep graphs
+        let mut topo = Vec::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![(self, false)];
+
+        while let Some((v, is_expanded)) = stack.pop() {
+            if is_expanded {
+                topo.push(v);
+            } else if visited.insert(v.node_ptr()) {
+                stack.push((v, true));
+                stack.extend(
+                    v.children
+                        .iter()
+                        .rev()
+                        .filter(|child| !visited.contains(&child.node_ptr()))
+                        .map(|child| (child, false)),
+                );
+            }
+        }
+
+        topo
+    }
+
+    /// Returns an identifier of the node: it is the same for all clones of the value and unique among
+    /// values which are alive at the same time (it can be reused after the node is dropped).
+    pub fn id(&self) -> NonZeroUsize {
+        NonZeroUsize::new(self.node_ptr() as usize).expect("pointer to shared data is never null")
+    }
+
+    pub(crate) fn node_ptr(&self) -> *const Lock<GradientData> {
+        Shared::as_ptr(&self.grad_data)
+    }
+
+    /// Returns numerically stable `ln(sum(exp(x_i)))` as a single node of the graph.
+    pub fn logsumexp(values: &[Value]) -> Value {
+        Self::new_nary(values, Op::Logsumexp, scalars::logsumexp, gradients::logsumexp, derivatives::logsumexp)
+    }
+
+    /// Returns fused multiply-add `a * b + c` as a single node of the graph.
+    pub fn fma(a: &Value, b: &Value, c: &Value) -> Value {
+        Self::new_nary(&[a.clone(), b.clone(), c.clone()], Op::Fma, scalars::fma, gradients::fma, derivatives::fma)
+    }
+
+    /// Returns a new node of the graph computed by a user defined unary operation: `data_fn` maps input
+    /// to output and `grad_fn` returns the local derivative given input and output data.
+    /// NOTE the derivative is not differentiable itself: `grad_graph` of it gives zero.
+    pub fn custom_unary(
+        &self,
+        data_fn: impl Fn(Float) -> Float + MaybeSync + 'static,
+        grad_fn: impl Fn(Float, Float) -> Float + MaybeSync + 'static,
+        name: impl Into<String>,
+    ) -> Value {
+        let name = name.into();
+        let (data_fn, grad_fn) = (Shared::new(data_fn), Shared::new(grad_fn));
+        let (gradient_data_fn, gradient_grad_fn) = (data_fn.clone(), grad_fn.clone());
+        let derivative_name = format!("{name}'");
+
+        self.new_unary(
+            Op::Custom { name },
+            move |lhs| data_fn(lhs),
+            move |lhs_gd, out_gd| {
+                let (lhs_data, out) = (lhs_gd.borrow().data, out_gd.borrow());
+                lhs_gd.borrow_mut().grad += grad_fn(lhs_data, out.data) * out.grad;
+            },
+            move |lhs, _out| {
+                let (data_fn, grad_fn) = (gradient_data_fn.clone(), gradient_grad_fn.clone());
+                lhs.new_unary(
+                    Op::Custom { name: derivative_name.clone() },
+                    move |lhs| grad_fn(lhs, data_fn(lhs)),
+                    |_, _| {},
+                    |_, _| Value::constant(0.),
+                )
+            },
+        )
+    }
+
+    /// Returns a new node of the graph computed by a user defined binary operation: `data_fn` maps inputs
+    /// to output and `grad_fn` returns local derivatives with respect to both inputs given their data.
+    /// NOTE the derivatives are not differentiable themselves: `grad_graph` of them gives zero.
+    pub fn custom_binary(
+        &self,
+        other: &Value,
+        data_fn: impl Fn(Float, Float) -> Float + MaybeSync + 'static,
+        grad_fn: impl Fn(Float, Float) -> (Float, Float) + MaybeSync + 'static,
+        name: impl Into<String>,
+    ) -> Value {
+        let name = name.into();
+        let grad_fn = Shared::new(grad_fn);
+        let derivative_grad_fn = grad_fn.clone();
+        let derivative_name = format!("{name}'");
+
+        Value::new_binary(
+            self,
+            other,
+            Op::Custom { name },
+            data_fn,
+            move |lhs_gd, rhs_gd, out_gd| {
+                let (lhs_data, rhs_data, out_grad) = (lhs_gd.borrow().data, rhs_gd.borrow().data, out_gd.borrow().grad);
+                let (lhs_grad, rhs_grad) = grad_fn(lhs_data, rhs_data);
+
+                lhs_gd.borrow_mut().grad += lhs_grad * out_grad;
+                rhs_gd.borrow_mut().grad += rhs_grad * out_grad;
+            },
+            move |lhs, rhs, _out| {
+                let derivative = |is_lhs: bool| {
+                    let grad_fn = derivative_grad_fn.clone();
+                    Value::new_binary(
+                        lhs,
+                        rhs,
+                        Op::Custom { name: derivative_name.clone() },
+                        move |lhs, rhs| if is_lhs { grad_fn(lhs, rhs).0 } else { grad_fn(lhs, rhs).1 },
+                        |_, _, _| {},
+                        |_, _, _| (Value::constant(0.), Value::constant(0.)),
+                    )
+                };
+                (derivative(true), derivative(false))
+            },
+        )
+    }
+
+    /// Lowers the graph into a flat sequence of instructions which can be evaluated and differentiated on new
+    /// data of given inputs without building the graph again. Other leaves are treated as constants.
+    pub fn compile(&self, inputs: &[Value]) -> super::CompiledGraph {
+        super::CompiledGraph::new(self, inputs)
+    }
+
+    /// Recomputes data of all nodes in the graph using current data of its leaves, so the graph
+    /// can be evaluated on new inputs (set via `set_data`) without building it again.
+    pub fn forward(&self) {
+        self.topological_order().iter().for_each(|v| {
+            if let Some(forward_fn) = v.forward_fn.as_ref() {
+                let data = forward_fn(v.children.iter().map(|child| child.get_data()).collect::<Vec<_>>().as_slice());
+                v.grad_data.borrow_mut().data = data;
+            }
+        });
+    }
+
+    fn new_unary(
+        &self,
+        op: Op,
+        data_fn: impl Fn(Float) -> Float + MaybeSync + 'static,
+        gradient_fn: impl Fn(&SharedGradientData, &SharedGradientData) + MaybeSync + 'static,
+        derivative_fn: impl Fn(&Value, &Value) -> Value + MaybeSync + 'static,
+    ) -> Self {
+        let grad_data = GradientData::new_shared(data_fn(self.get_data()));
+        if !is_grad_enabled() {
+            return Value::new_untracked(grad_data, op);
+        }
+
+        let (lhs_gd, out_gd) = (Shared::downgrade(&self.grad_data), Shared::downgrade(&grad_data));
+
+        let gradient_fn: GradientFn = Shared::new(move |children: &[SharedGradientData], out: &SharedGradientData| {
+            gradient_fn(&children[0], out)
+        });
+        let backward_gradient_fn = gradient_fn.clone();
+        let backward_fn: Option<BackwardFn> = Some(Shared::new(Box::new(move || {
+            lhs_gd
+                .upgrade()
+                .zip(out_gd.upgrade())
+                .iter()
+                .for_each(|(lhs_gd, out_gd)| backward_gradient_fn(std::slice::from_ref(lhs_gd), out_gd))
+        })));
+        let forward_fn: Option<ForwardFn> = Some(Shared::new(move |data: &[Float]| data_fn(data[0])));
+        let derivative_fn: Option<DerivativeFn> =
+            Some(Shared::new(move |children: &[Value], out: &Value| vec![derivative_fn(&children[0], out)]));
+
+        let children = Shared::new(vec![self.clone()]);
+        Value {
+            grad_data,
+            children,
+            backward_fn,
+            forward_fn,
+            gradient_fn: Some(gradient_fn),
+            derivative_fn,
+            op,
+            topo_cache: Once::default(),
+        }
+    }
+
+    fn new_binary(
+        lhs: &Value,
+        rhs: &Value,
+        op: Op,
+        data_fn: impl Fn(Float, Float) -> Float + MaybeSync + 'static,
+        gradient_fn: impl Fn(&SharedGradientData, &SharedGradientData, &SharedGradientData) + MaybeSync + 'static,
+        derivative_fn: impl Fn(&Value, &Value, &Value) -> (Value, Value) + MaybeSync + 'static,
+    ) -> Self {
+        let grad_data = GradientData::new_shared(data_fn(lhs.get_data(), rhs.get_data()));
+        if !is_grad_enabled() {
+            return Value::new_untracked(grad_data, op);
+        }
+
+        let (lhs_gd, rhs_gd, out_gd) =
+            (Shared::downgrade(&lhs.grad_data), Shared::downgrade(&rhs.grad_data), Shared::downgrade(&grad_data));
+
+        // NOTE the same operand used twice is stored as a single child
+        let gradient_fn: GradientFn = Shared::new(move |children: &[SharedGradientData], out: &SharedGradientData| {
+            gradient_fn(&children[0], &children[children.len() - 1], out)
+        });
+        let backward_gradient_fn = gradient_fn.clone();
+        let backward_fn: Option<BackwardFn> = Some(Shared::new(Box::new(move || {
+            lhs_gd
+                .upgrade()
+                .zip(rhs_gd.upgrade())
+                .zip(out_gd.upgrade())
+                .into_iter()
+                .for_each(|((lhs_gd, rhs_gd), out_gd)| backward_gradient_fn(&[lhs_gd, rhs_gd], &out_gd))
+        })));
+        let forward_fn: Option<ForwardFn> =
+            Some(Shared::new(move |data: &[Float]| data_fn(data[0], data[data.len() - 1])));
+        let derivative_fn: Option<DerivativeFn> = Some(Shared::new(move |children: &[Value], out: &Value| {
+            match derivative_fn(&children[0], &children[children.len() - 1], out) {
+                (lhs_derivative, rhs_derivative) if children.len() == 1 => vec![lhs_derivative + rhs_derivative],
+                (lhs_derivative, rhs_derivative) => vec![lhs_derivative, rhs_derivative],
+            }
+        }));
+
+        let children = if Shared::ptr_eq(&lhs.grad_data, &rhs.grad_data) {
+            vec![lhs.clone()]
+        } else {
+            vec![lhs.clone(), rhs.clone()]
+        };
+
+        let children = Shared::new(children);
+        Value {
+            grad_data,
+            children,
+            backward_fn,
+            forward_fn,
+            gradient_fn: Some(gradient_fn),
+            derivative_fn,
+            op,
+            topo_cache: Once::default(),
+        }
+    }
+
+    fn new_nary(
+        children: &[Value],
+        op: Op,
+        data_fn: impl Fn(&[Float]) -> Float + MaybeSync + 'static,
+        gradient_fn: impl Fn(&[SharedGradientData], &SharedGradientData) + MaybeSync + 'static,
+        derivative_fn: impl Fn(&[Value], &Value) -> Vec<Value> + MaybeSync + 'static,
+    ) -> Self {
+        let data = data_fn(children.iter().map(|child| child.get_data()).collect::<Vec<_>>().as_slice());
+        let grad_data = GradientData::new_shared(data);
+        if !is_grad_enabled() {
+            return Value::new_untracked(grad_data, op);
+        }
+
+        let children_gd = children.iter().map(|child| Shared::downgrade(&child.grad_data)).collect::<Vec<_>>();
+        let out_gd = Shared::downgrade(&grad_data);
+
+        let gradient_fn: GradientFn = Shared::new(gradient_fn);
+        let backward_gradient_fn = gradient_fn.clone();
+        let backward_fn: Option<BackwardFn> = Some(Shared::new(Box::new(move || {
+            children_gd
+                .iter()
+                .map(|child_gd| child_gd.upgrade())
+                .collect::<Option<Vec<_>>>()
+                .zip(out_gd.upgrade())
+                .iter()
+                .for_each(|(children_gd, out_gd)| backward_gradient_fn(children_gd.as_slice(), out_gd))
+        })));
+        let forward_fn: Option<ForwardFn> = Some(Shared::new(data_fn));
+        let derivative_fn: Option<DerivativeFn> = Some(Shared::new(derivative_fn));
+
+        let children = Shared::new(children.to_vec());
+        Value {
+            grad_data,
+            children,
+            backward_fn,
+            forward_fn,
+            gradient_fn: Some(gradient_fn),
+            derivative_fn,
+            op,
+            topo_cache: Once::default(),
+        }
+    }
+
+    /// Returns square root. Gradient is guarded against division by zero with a small epsilon.
+    pub fn sqrt(&self) -> Value {
+        let mut value = self.sqrt_eps(1E-12);
+        value.op = Op::Sqrt;
+        value
+    }
+}
+
+mod parallel {
+    use super::*;
+
+    /// Levels narrower than this are processed sequentially as parallelism does not pay off there.
+    const MIN_PARALLEL_WIDTH: usize = 32;
+
+    /// Applies backward steps given in reverse topological order level by level: a level of a node is
+    /// the longest distance from the output, so nodes of the same level do not depend on each other.
+    pub(super) fn backward(steps: Vec<(&Value, Option<Float>, bool)>) {
+        let mut depths: HashMap<NonZeroUsize, usize> = HashMap::new();
+        let mut levels: Vec<Vec<_>> = Vec::new();
+
+        steps.into_iter().for_each(|step| {
+            let level = depths.get(&step.0.id()).copied().unwrap_or(0);
+            step.0.children.iter().for_each(|child| {
+                let depth = depths.entry(child.id()).or_default();
+                *depth = (*depth).max(level + 1);
+            });
+
+            if levels.len() <= level {
+                levels.resize_with(level + 1, Vec::default);
+            }
+            levels[level].push(step);
+        });
+
+        levels.into_iter().for_each(|level| {
+            let apply =
+                |&(v, previous, is_expanded): &(&Value, Option<Float>, bool)| v.backward_step(previous, is_expanded);
+            if level.len() < MIN_PARALLEL_WIDTH {
+                level.iter().for_each(apply);
+            } else {
+                shared::for_each_parallel(level.as_slice(), apply);
+            }
+        });
+    }
+}
+
+mod gradients {
+    use super::*;
+
+    pub(crate) fn add(lhs: &SharedGradientData, rhs: &SharedGradientData, out: &SharedGradientData) {
+        let out_grad = out.borrow().grad;
+
+        if Shared::ptr_eq(lhs, rhs) {
+            lhs.borrow_mut().grad += 2. * out_grad;
+        } else {
+            lhs.borrow_mut().grad += out_grad;
+            rhs.borrow_mut().grad += out_grad;
+        }
+    }
+
+    pub(crate) fn mul(lhs: &SharedGradientData, rhs: &SharedGradientData, out: &SharedGradientData) {
+        let out_grad = out.borrow().grad;
+
+        if Shared::ptr_eq(lhs, rhs) {
+            let lhs_data = lhs.borrow().data;
+            lhs.borrow_mut().grad += 2. * (lhs_data * out_grad);
+        } else {
+            // NOTE read data first: holding a lock of one operand while updating another can deadlock
+            // when nodes are processed in parallel
+            let (lhs_data, rhs_data) = (lhs.borrow().data, rhs.borrow().data);
+            lhs.borrow_mut().grad += rhs_data * out_grad;
+            rhs.borrow_mut().grad += lhs_data * out_grad;
+        }
+    }
+
+    pub(crate) fn sub(lhs: &SharedGradientData, rhs: &SharedGradientData, out: &SharedGradientData) {
+        let out_grad = out.borrow().grad;
+
+        lhs.borrow_mut().grad += out_grad;
+        rhs.borrow_mut().grad -= out_grad;
+    }
+
+    pub(crate) fn div(lhs: &SharedGradientData, rhs: &SharedGradientData, out: &SharedGradientData) {
+        let (lhs_data, rhs_data) = (lhs.borrow().data, rhs.borrow().data);
+        let out_grad = out.borrow().grad;
+
+        lhs.borrow_mut().grad += out_grad / rhs_data;
+        rhs.borrow_mut().grad += -lhs_data / (rhs_data * rhs_data) * out_grad;
+    }
+
+    pub(crate) fn powf(lhs: &SharedGradientData, rhs: Float, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += (rhs * lhs_data.powf(rhs - 1.)) * out.borrow().grad;
+    }
+
+    pub(crate) fn relu(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let out = out.borrow();
+
+        lhs.borrow_mut().grad += if out.data > 0. { out.grad } else { 0. };
+    }
+
+    pub(crate) fn tanh(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let out = out.borrow();
+
+        lhs.borrow_mut().grad += (1. - out.data * out.data) * out.grad;
+    }
+
+    pub(crate) fn sigmoid(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let out = out.borrow();
+
+        lhs.borrow_mut().grad += out.data * (1. - out.data) * out.grad;
+    }
+
+    pub(crate) fn exp(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let out = out.borrow();
+
+        lhs.borrow_mut().grad += out.data * out.grad;
+    }
+
+    pub(crate) fn ln(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += out.borrow().grad / lhs_data;
+    }
+
+    pub(crate) fn log(lhs: &SharedGradientData, base: Float, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += out.borrow().grad / (lhs_data * base.ln());
+    }
+
+    pub(crate) fn sqrt(lhs: &SharedGradientData, eps: Float, out: &SharedGradientData) {
+        let out = out.borrow();
+
+        lhs.borrow_mut().grad += 0.5 / (out.data + eps) * out.grad;
+    }
+
+    pub(crate) fn abs(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += scalars::sign(lhs_data) * out.borrow().grad;
+    }
+
+    pub(crate) fn log2(lhs: &SharedGradientData, out: &SharedGradientData) {
+        log(lhs, 2., out)
+    }
+
+    pub(crate) fn log10(lhs: &SharedGradientData, out: &SharedGradientData) {
+        log(lhs, 10., out)
+    }
+
+    pub(crate) fn sin(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += lhs_data.cos() * out.borrow().grad;
+    }
+
+    pub(crate) fn cos(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += -lhs_data.sin() * out.borrow().grad;
+    }
+
+    pub(crate) fn tan(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += out.borrow().grad / lhs_data.cos().powi(2);
+    }
+
+    pub(crate) fn atan(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += out.borrow().grad / (1. + lhs_data * lhs_data);
+    }
+
+    pub(crate) fn sinh(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += lhs_data.cosh() * out.borrow().grad;
+    }
+
+    pub(crate) fn cosh(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += lhs_data.sinh() * out.borrow().grad;
+    }
+
+    pub(crate) fn elu(lhs: &SharedGradientData, alpha: Float, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        let local_grad = if lhs_data > 0. { 1. } else { alpha * lhs_data.exp() };
+        lhs.borrow_mut().grad += local_grad * out.borrow().grad;
+    }
+
+    pub(crate) fn silu(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        let sigmoid = scalars::sigmoid(lhs_data);
+        lhs.borrow_mut().grad += sigmoid * (1. + lhs_data * (1. - sigmoid)) * out.borrow().grad;
+    }
+
+    pub(crate) fn softplus(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += scalars::sigmoid(lhs_data) * out.borrow().grad;
+    }
+
+    pub(crate) fn clamp(lhs: &SharedGradientData, lo: Float, hi: Float, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += if lhs_data >= lo && lhs_data <= hi { out.borrow().grad } else { 0. };
+    }
+
+    pub(crate) fn max(lhs: &SharedGradientData, rhs: &SharedGradientData, out: &SharedGradientData) {
+        let (lhs_data, rhs_data) = (lhs.borrow().data, rhs.borrow().data);
+        let (lhs_share, rhs_share) = extremum_shares(lhs_data > rhs_data, lhs_data == rhs_data);
+        let out_grad = out.borrow().grad;
+
+        lhs.borrow_mut().grad += lhs_share * out_grad;
+        rhs.borrow_mut().grad += rhs_share * out_grad;
+    }
+
+    pub(crate) fn min(lhs: &SharedGradientData, rhs: &SharedGradientData, out: &SharedGradientData) {
+        let (lhs_data, rhs_data) = (lhs.borrow().data, rhs.borrow().data);
+        let (lhs_share, rhs_share) = extremum_shares(lhs_data < rhs_data, lhs_data == rhs_data);
+        let out_grad = out.borrow().grad;
+
+        lhs.borrow_mut().grad += lhs_share * out_grad;
+        rhs.borrow_mut().grad += rhs_share * out_grad;
+    }
+
+    pub(crate) fn extremum_shares(is_lhs: bool, is_tie: bool) -> (Float, Float) {
+        match (is_lhs, is_tie) {
+            (_, true) => (0.5, 0.5),
+            (true, false) => (1., 0.),
+            (false, false) => (0., 1.),
+        }
+    }
+
+    pub(crate) fn sign(lhs: &SharedGradientData, out: &SharedGradientData) {
+        zero(lhs, out)
+    }
+
+    pub(crate) fn floor(lhs: &SharedGradientData, out: &SharedGradientData) {
+        zero(lhs, out)
+    }
+
+    pub(crate) fn ceil(lhs: &SharedGradientData, out: &SharedGradientData) {
+        zero(lhs, out)
+    }
+
+    pub(crate) fn round(lhs: &SharedGradientData, out: &SharedGradientData) {
+        zero(lhs, out)
+    }
+
+    fn zero(_lhs: &SharedGradientData, _out: &SharedGradientData) {
+        // step functions have zero gradient almost everywhere
+    }
+
+    pub(crate) fn erf(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        let local_grad = consts::FRAC_2_SQRT_PI * (-lhs_data * lhs_data).exp();
+        lhs.borrow_mut().grad += local_grad * out.borrow().grad;
+    }
+
+    pub(crate) fn powi(lhs: &SharedGradientData, rhs: i32, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += (rhs as Float * lhs_data.powi(rhs - 1)) * out.borrow().grad;
+    }
+
+    pub(crate) fn pow(lhs: &SharedGradientData, rhs: &SharedGradientData, out: &SharedGradientData) {
+        let (lhs_data, rhs_data) = (lhs.borrow().data, rhs.borrow().data);
+        let (out_data, out_grad) = (out.borrow().data, out.borrow().grad);
+        let rhs_local_grad = if lhs_data == 0. { 0. } else { out_data * lhs_data.ln() };
+
+        lhs.borrow_mut().grad += rhs_data * lhs_data.powf(rhs_data - 1.) * out_grad;
+        rhs.borrow_mut().grad += rhs_local_grad * out_grad;
+    }
+
+    pub(crate) fn recip(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let out = out.borrow();
+
+        lhs.borrow_mut().grad += -out.data * out.data * out.grad;
+    }
+
+    pub(crate) fn expm1(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let out = out.borrow();
+
+        lhs.borrow_mut().grad += (out.data + 1.) * out.grad;
+    }
+
+    pub(crate) fn ln_1p(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += out.borrow().grad / (1. + lhs_data);
+    }
+
+    pub(crate) fn logsumexp(values: &[SharedGradientData], out: &SharedGradientData) {
+        let (out_data, out_grad) = (out.borrow().data, out.borrow().grad);
+
+        values.iter().for_each(|value| {
+            let softmax = (value.borrow().data - out_data).exp();
+            value.borrow_mut().grad += softmax * out_grad;
+        });
+    }
+
+    pub(crate) fn fma(values: &[SharedGradientData], out: &SharedGradientData) {
+        let (a_data, b_data, out_grad) = (values[0].borrow().data, values[1].borrow().data, out.borrow().grad);
+
+        values[0].borrow_mut().grad += b_data * out_grad;
+        values[1].borrow_mut().grad += a_data * out_grad;
+        values[2].borrow_mut().grad += out_grad;
+    }
+
+    pub(crate) fn atan2(lhs: &SharedGradientData, rhs: &SharedGradientData, out: &SharedGradientData) {
+        let (y, x) = (lhs.borrow().data, rhs.borrow().data);
+        let out_grad = out.borrow().grad;
+        let denominator = x * x + y * y;
+
+        lhs.borrow_mut().grad += x / denominator * out_grad;
+        rhs.borrow_mut().grad += -y / denominator * out_grad;
+    }
+
+    pub(crate) fn sign_ste(lhs: &SharedGradientData, out: &SharedGradientData) {
+        straight_through(lhs, out)
+    }
+
+    pub(crate) fn round_ste(lhs: &SharedGradientData, out: &SharedGradientData) {
+        straight_through(lhs, out)
+    }
+
+    fn straight_through(lhs: &SharedGradientData, out: &SharedGradientData) {
+        lhs.borrow_mut().grad += out.borrow().grad;
+    }
+
+    pub(crate) fn safe_ln(lhs: &SharedGradientData, eps: Float, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += out.borrow().grad / lhs_data.max(eps);
+    }
+
+    pub(crate) fn softsign(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += out.borrow().grad / (1. + lhs_data.abs()).powi(2);
+    }
+
+    pub(crate) fn hard_tanh(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += if lhs_data > -1. && lhs_data < 1. { out.borrow().grad } else { 0. };
+    }
+
+    pub(crate) fn hard_sigmoid(lhs: &SharedGradientData, out: &SharedGradientData) {
+        let lhs_data = lhs.borrow().data;
+        lhs.borrow_mut().grad += if lhs_data > -3. && lhs_data < 3. { out.borrow().grad / 6. } else { 0. };
+    }
 }
 
-impl Value {
-    pub(crate) fn new(data: f64) -> Self {
-        let grad_data = GradientData::new_shared(data);
-        Self { grad_data, children: vec![], backward_fn: None, op: "".to_string() }
+mod derivatives {
+    use super::*;
+
+    fn constant(data: Float) -> Value {
+        Value::constant(data)
+    }
+
+    pub(crate) fn add(_lhs: &Value, _rhs: &Value, _out: &Value) -> (Value, Value) {
+        (constant(1.), constant(1.))
+    }
+
+    pub(crate) fn mul(lhs: &Value, rhs: &Value, _out: &Value) -> (Value, Value) {
+        (rhs.clone(), lhs.clone())
+    }
+
+    pub(crate) fn sub(_lhs: &Value, _rhs: &Value, _out: &Value) -> (Value, Value) {
+        (constant(1.), constant(-1.))
+    }
+
+    pub(crate) fn div(lhs: &Value, rhs: &Value, _out: &Value) -> (Value, Value) {
+        (rhs.recip(), -(lhs / rhs.powi(2)))
+    }
+
+    pub(crate) fn powf(lhs: &Value, rhs: Float, _out: &Value) -> Value {
+        rhs * lhs.pow(rhs - 1.)
+    }
+
+    pub(crate) fn relu(_lhs: &Value, out: &Value) -> Value {
+        constant(if out.get_data() > 0. { 1. } else { 0. })
+    }
+
+    pub(crate) fn tanh(_lhs: &Value, out: &Value) -> Value {
+        1. - out.powi(2)
+    }
+
+    pub(crate) fn sigmoid(_lhs: &Value, out: &Value) -> Value {
+        out * (1. - out)
+    }
+
+    pub(crate) fn exp(_lhs: &Value, out: &Value) -> Value {
+        out.clone()
     }
 
-    /// Returns underlying data.
-    pub fn get_data(&self) -> f64 {
-        self.grad_data.borrow().data
+    pub(crate) fn ln(lhs: &Value, _out: &Value) -> Value {
+        lhs.recip()
     }
 
-    pub fn set_data(&mut self, value: f64) {
-        self.grad_data.borrow_mut().data = value;
+    pub(crate) fn log(lhs: &Value, base: Float, _out: &Value) -> Value {
+        (lhs * base.ln()).recip()
     }
 
-    /// Returns a gradient.
-    pub fn get_grad(&self) -> f64 {
-        self.grad_data.borrow().grad
+    pub(crate) fn sqrt(_lhs: &Value, eps: Float, out: &Value) -> Value {
+        0.5 / (out + eps)
     }
 
-    /// Sets gradient to zero.
-    pub fn zero_grad(&mut self) {
-        self.grad_data.borrow_mut().grad = 0.;
+    pub(crate) fn abs(lhs: &Value, _out: &Value) -> Value {
+        lhs.sign()
     }
 
-    /// Applies gradients.
-    pub fn backward(&self) {
-        // topological order all of the children in the graph
-        let topo = RefCell::new(Vec::new());
-        let visited = RefCell::new(HashSet::new());
-
-        fn build_topo<'a>(v: &'a Value, topo: &RefCell<Vec<&'a Value>>, visited: &RefCell<HashSet<&'a Value>>) {
-            if !visited.borrow().contains(&v) {
-                visited.borrow_mut().insert(v);
-                v.children.iter().for_each(|child| build_topo(child, topo, visited));
-                topo.borrow_mut().push(v)
-            }
-        }
-        build_topo(self, &topo, &visited);
+    pub(crate) fn log2(lhs: &Value, out: &Value) -> Value {
+        log(lhs, 2., out)
+    }
 
-        // go one variable at a time and apply the chain rule to get its gradient
-        self.grad_data.borrow_mut().grad = 1.;
-        topo.borrow().iter().rev().filter_map(|v| v.backward_fn.as_ref()).for_each(|backward| backward());
+    pub(crate) fn log10(lhs: &Value, out: &Value) -> Value {
+        log(lhs, 10., out)
     }
-}
 
-mod gradients {
-    use super::*;
+    pub(crate) fn sin(lhs: &Value, _out: &Value) -> Value {
+        lhs.cos()
+    }
 
-    pub(crate) fn add(lhs: &SharedGradientData, rhs: &SharedGradientData, out: &SharedGradientData) {
-        let out_grad = out.borrow().grad;
+    pub(crate) fn cos(lhs: &Value, _out: &Value) -> Value {
+        -lhs.sin()
+    }
 
-        if Rc::ptr_eq(lhs, rhs) {
-            lhs.borrow_mut().grad += 2. * out_grad;
+    pub(crate) fn tan(lhs: &Value, _out: &Value) -> Value {
+        lhs.cos().powi(-2)
+    }
+
+    pub(crate) fn atan(lhs: &Value, _out: &Value) -> Value {
+        (1. + lhs.powi(2)).recip()
+    }
+
+    pub(crate) fn sinh(lhs: &Value, _out: &Value) -> Value {
+        lhs.cosh()
+    }
+
+    pub(crate) fn cosh(lhs: &Value, _out: &Value) -> Value {
+        lhs.sinh()
+    }
+
+    pub(crate) fn elu(lhs: &Value, alpha: Float, _out: &Value) -> Value {
+        if lhs.get_data() > 0. {
+            constant(1.)
         } else {
-            lhs.borrow_mut().grad += out_grad;
-            rhs.borrow_mut().grad += out_grad;
+            alpha * lhs.exp()
         }
     }
 
-    pub(crate) fn mul(lhs: &SharedGradientData, rhs: &SharedGradientData, out: &SharedGradientData) {
-        let out_grad = out.borrow().grad;
+    pub(crate) fn silu(lhs: &Value, _out: &Value) -> Value {
+        let sigmoid = lhs.sigmoid();
+        &sigmoid * (1. + lhs * (1. - &sigmoid))
+    }
 
-        if Rc::ptr_eq(lhs, rhs) {
-            let lhs_data = lhs.borrow().data;
-            lhs.borrow_mut().grad += 2. * (lhs_data * out_grad);
+    pub(crate) fn softplus(lhs: &Value, _out: &Value) -> Value {
+        lhs.sigmoid()
+    }
+
+    pub(crate) fn clamp(lhs: &Value, lo: Float, hi: Float, _out: &Value) -> Value {
+        let lhs_data = lhs.get_data();
+        constant(if lhs_data >= lo && lhs_data <= hi { 1. } else { 0. })
+    }
+
+    pub(crate) fn max(lhs: &Value, rhs: &Value, _out: &Value) -> (Value, Value) {
+        let (lhs_data, rhs_data) = (lhs.get_data(), rhs.get_data());
+        let (lhs_share, rhs_share) = gradients::extremum_shares(lhs_data > rhs_data, lhs_data == rhs_data);
+
+        (constant(lhs_share), constant(rhs_share))
+    }
+
+    pub(crate) fn min(lhs: &Value, rhs: &Value, _out: &Value) -> (Value, Value) {
+        let (lhs_data, rhs_data) = (lhs.get_data(), rhs.get_data());
+        let (lhs_share, rhs_share) = gradients::extremum_shares(lhs_data < rhs_data, lhs_data == rhs_data);
+
+        (constant(lhs_share), constant(rhs_share))
+    }
+
+    pub(crate) fn sign(_lhs: &Value, _out: &Value) -> Value {
+        constant(0.)
+    }
+
+    pub(crate) fn floor(_lhs: &Value, _out: &Value) -> Value {
+        constant(0.)
+    }
+
+    pub(crate) fn ceil(_lhs: &Value, _out: &Value) -> Value {
+        constant(0.)
+    }
+
+    pub(crate) fn round(_lhs: &Value, _out: &Value) -> Value {
+        constant(0.)
+    }
+
+    pub(crate) fn erf(lhs: &Value, _out: &Value) -> Value {
+        consts::FRAC_2_SQRT_PI * (-lhs.powi(2)).exp()
+    }
+
+    pub(crate) fn powi(lhs: &Value, rhs: i32, _out: &Value) -> Value {
+        rhs as Float * lhs.powi(rhs - 1)
+    }
+
+    pub(crate) fn pow(lhs: &Value, rhs: &Value, out: &Value) -> (Value, Value) {
+        let rhs_derivative = if lhs.get_data() == 0. { constant(0.) } else { out * lhs.ln() };
+
+        (rhs * lhs.pow_value(&(rhs - 1.)), rhs_derivative)
+    }
+
+    pub(crate) fn recip(_lhs: &Value, out: &Value) -> Value {
+        -out.powi(2)
+    }
+
+    pub(crate) fn expm1(_lhs: &Value, out: &Value) -> Value {
+        out + 1.
+    }
+
+    pub(crate) fn ln_1p(lhs: &Value, _out: &Value) -> Value {
+        (1. + lhs).recip()
+    }
+
+    pub(crate) fn logsumexp(values: &[Value], out: &Value) -> Vec<Value> {
+        values.iter().map(|value| (value - out).exp()).collect()
+    }
+
+    pub(crate) fn fma(values: &[Value], _out: &Value) -> Vec<Value> {
+        vec![values[1].clone(), values[0].clone(), constant(1.)]
+    }
+
+    pub(crate) fn atan2(lhs: &Value, rhs: &Value, _out: &Value) -> (Value, Value) {
+        let denominator = rhs.powi(2) + lhs.powi(2);
+
+        (rhs / &denominator, -(lhs / &denominator))
+    }
+
+    pub(crate) fn sign_ste(_lhs: &Value, _out: &Value) -> Value {
+        constant(1.)
+    }
+
+    pub(crate) fn round_ste(_lhs: &Value, _out: &Value) -> Value {
+        constant(1.)
+    }
+
+    pub(crate) fn safe_ln(lhs: &Value, eps: Float, _out: &Value) -> Value {
+        if lhs.get_data() > eps {
+            lhs.recip()
         } else {
-            lhs.borrow_mut().grad += rhs.borrow().data * out_grad;
-            rhs.borrow_mut().grad += lhs.borrow().data * out_grad;
+            constant(1. / eps)
         }
     }
 
-    pub(crate) fn powf(lhs: &SharedGradientData, rhs: f64, out: &SharedGradientData) {
-        let lhs_data = lhs.borrow().data;
-        lhs.borrow_mut().grad += (rhs * lhs_data.powf(rhs - 1.)) * out.borrow().grad;
+    pub(crate) fn softsign(lhs: &Value, _out: &Value) -> Value {
+        (1. + lhs.abs()).powi(-2)
     }
 
-    pub(crate) fn relu(lhs: &SharedGradientData, out: &SharedGradientData) {
-        let out = out.borrow();
+    pub(crate) fn hard_tanh(lhs: &Value, _out: &Value) -> Value {
+        let lhs_data = lhs.get_data();
+        constant(if lhs_data > -1. && lhs_data < 1. { 1. } else { 0. })
+    }
 
-        lhs.borrow_mut().grad += if out.data > 0. { out.grad } else { 0. };
+    pub(crate) fn hard_sigmoid(lhs: &Value, _out: &Value) -> Value {
+        let lhs_data = lhs.get_data();
+        constant(if lhs_data > -3. && lhs_data < 3. { 1. / 6. } else { 0. })
     }
 }
 
 mod scalars {
-    pub fn powf(lhs: f64, rhs: f64) -> f64 {
+    use super::super::float::{consts, Float};
+
+    pub fn powf(lhs: Float, rhs: Float) -> Float {
         lhs.powf(rhs)
     }
 
-    pub fn relu(value: f64) -> f64 {
+    pub fn relu(value: Float) -> Float {
         value.max(0.)
     }
+
+    pub fn tanh(value: Float) -> Float {
+        value.tanh()
+    }
+
+    pub fn sigmoid(value: Float) -> Float {
+        // avoid overflow of exp for large negative values
+        if value >= 0. {
+            1. / (1. + (-value).exp())
+        } else {
+            let exp = value.exp();
+            exp / (1. + exp)
+        }
+    }
+
+    pub fn exp(value: Float) -> Float {
+        value.exp()
+    }
+
+    pub fn ln(value: Float) -> Float {
+        if value > 0. {
+            value.ln()
+        } else {
+            Float::NAN
+        }
+    }
+
+    pub fn log(value: Float, base: Float) -> Float {
+        ln(value) / base.ln()
+    }
+
+    pub fn sqrt(value: Float, _eps: Float) -> Float {
+        value.sqrt()
+    }
+
+    pub fn abs(value: Float) -> Float {
+        value.abs()
+    }
+
+    pub fn sign(value: Float) -> Float {
+        if value > 0. {
+            1.
+        } else if value < 0. {
+            -1.
+        } else {
+            0.
+        }
+    }
+
+    pub fn log2(value: Float) -> Float {
+        ln(value) / consts::LN_2
+    }
+
+    pub fn log10(value: Float) -> Float {
+        ln(value) / consts::LN_10
+    }
+
+    pub fn sin(value: Float) -> Float {
+        value.sin()
+    }
+
+    pub fn cos(value: Float) -> Float {
+        value.cos()
+    }
+
+    pub fn tan(value: Float) -> Float {
+        value.tan()
+    }
+
+    pub fn atan(value: Float) -> Float {
+        value.atan()
+    }
+
+    pub fn sinh(value: Float) -> Float {
+        value.sinh()
+    }
+
+    pub fn cosh(value: Float) -> Float {
+        value.cosh()
+    }
+
+    pub fn elu(value: Float, alpha: Float) -> Float {
+        if value > 0. {
+            value
+        } else {
+            alpha * value.exp_m1()
+        }
+    }
+
+    pub fn silu(value: Float) -> Float {
+        value * sigmoid(value)
+    }
+
+    pub fn softplus(value: Float) -> Float {
+        // ln(1 + e^x) = max(x, 0) + ln(1 + e^-|x|) does not overflow for large x
+        value.max(0.) + (-value.abs()).exp().ln_1p()
+    }
+
+    pub fn clamp(value: Float, lo: Float, hi: Float) -> Float {
+        value.max(lo).min(hi)
+    }
+
+    pub fn max(lhs: Float, rhs: Float) -> Float {
+        lhs.max(rhs)
+    }
+
+    pub fn min(lhs: Float, rhs: Float) -> Float {
+        lhs.min(rhs)
+    }
+
+    pub fn erf(value: Float) -> Float {
+        if value.is_nan() {
+            return value;
+        }
+
+        if value < 0. {
+            return -erf(-value);
+        }
+
+        if value < 3. {
+            // Maclaurin series: 2/sqrt(pi) * sum((-1)^n * x^(2n+1) / (n! * (2n+1)))
+            let (mut sum, mut term, mut n) = (value, value, 0.);
+            loop {
+                n += 1.;
+                term *= -value * value / n;
+                let delta = term / (2. * n + 1.);
+                sum += delta;
+                if delta.abs() <= 1E-17 * sum.abs() {
+                    break;
+                }
+            }
+            consts::FRAC_2_SQRT_PI * sum
+        } else {
+            // continued fraction for erfc which converges quickly for large values
+            let fraction = (1..=60).rev().fold(value, |acc, n| value + (n as Float / 2.) / acc);
+            1. - (-value * value).exp() / (consts::PI.sqrt() * fraction)
+        }
+    }
+
+    pub fn powi(lhs: Float, rhs: i32) -> Float {
+        lhs.powi(rhs)
+    }
+
+    pub fn pow(lhs: Float, rhs: Float) -> Float {
+        lhs.powf(rhs)
+    }
+
+    pub fn recip(value: Float) -> Float {
+        value.recip()
+    }
+
+    pub fn expm1(value: Float) -> Float {
+        value.exp_m1()
+    }
+
+    pub fn ln_1p(value: Float) -> Float {
+        if value > -1. {
+            value.ln_1p()
+        } else {
+            Float::NAN
+        }
+    }
+
+    pub fn floor(value: Float) -> Float {
+        value.floor()
+    }
+
+    pub fn ceil(value: Float) -> Float {
+        value.ceil()
+    }
+
+    pub fn round(value: Float) -> Float {
+        value.round()
+    }
+
+    pub fn atan2(lhs: Float, rhs: Float) -> Float {
+        lhs.atan2(rhs)
+    }
+
+    pub fn sign_ste(value: Float) -> Float {
+        sign(value)
+    }
+
+    pub fn round_ste(value: Float) -> Float {
+        round(value)
+    }
+
+    pub fn safe_ln(value: Float, eps: Float) -> Float {
+        value.max(eps).ln()
+    }
+
+    pub fn softsign(value: Float) -> Float {
+        value / (1. + value.abs())
+    }
+
+    pub fn hard_tanh(value: Float) -> Float {
+        clamp(value, -1., 1.)
+    }
+
+    pub fn hard_sigmoid(value: Float) -> Float {
+        clamp(value / 6. + 0.5, 0., 1.)
+    }
+
+    pub fn fma(values: &[Float]) -> Float {
+        values[0] * values[1] + values[2]
+    }
+
+    pub fn logsumexp(values: &[Float]) -> Float {
+        let max = values.iter().cloned().fold(Float::NEG_INFINITY, Float::max);
+        if max.is_finite() {
+            max + values.iter().map(|value| (value - max).exp()).sum::<Float>().ln()
+        } else {
+            max
+        }
+    }
 }
 
 macro_rules! custom_operator_impl {
-    (use $fn_name: ident for $type_: ident { fn $method: ident$( with $v:tt: $t:ty)? }) => {
+    (use $fn_name: ident for $type_: ident { $(#[$meta:meta])* fn $method: ident as $variant: ident$( with $($v:tt: $t:ty),+)? }) => {
         impl $type_ {
-            pub fn $method(&self$(, $v: $t)?) -> $type_ {
-                let grad_data = GradientData::new_shared(scalars::$fn_name(self.get_data() $(,$v)?));
-                let (lhs_gd, out_gd) = (Rc::downgrade(&self.grad_data), Rc::downgrade(&grad_data));
-
-                let backward_fn: Option<BackwardFn> = Some(Rc::new(Box::new(move || {
-                    lhs_gd.upgrade().zip(out_gd.upgrade()).iter()
-                        .for_each(|(lhs_gd, out_gd)| gradients::$fn_name(lhs_gd, $($v,)? out_gd))
-                    })
-                ));
-
-                let op = String::from(stringify!($method));
-                Value { grad_data, children: vec![self.clone()], backward_fn, op }
+            $(#[$meta])*
+            pub fn $method(&self$($(, $v: $t)+)?) -> $type_ {
+                self.new_unary(
+                    Op::$variant$({ $($v),+ })?,
+                    move |lhs| scalars::$fn_name(lhs $($(, $v)+)?),
+                    move |lhs_gd, out_gd| gradients::$fn_name(lhs_gd, $($($v,)+)? out_gd),
+                    move |lhs, out| derivatives::$fn_name(lhs, $($($v,)+)? out),
+                )
             }
         }
     };
 }
 
-macro_rules! binary_operator_impl {
-    (impl $op:tt for $type_: ident with fn $method: ident and reverse $op_rev:tt fn $method_rev: ident by ($reverse_val: ident, $reverse_arg: ident) ) => {
-        fn $method(lhs: &$type_, rhs: &$type_) -> $type_ {
-            let grad_data = GradientData::new_shared(lhs.get_data().$method(&rhs.get_data()));
-            let (lhs_gd, rhs_gd, out_gd) =
-                (Rc::downgrade(&lhs.grad_data), Rc::downgrade(&rhs.grad_data), Rc::downgrade(&grad_data));
-
-            let backward_fn: Option<BackwardFn> = Some(Rc::new(Box::new(move || {
-                lhs_gd
-                    .upgrade()
-                    .zip(rhs_gd.upgrade())
-                    .zip(out_gd.upgrade())
-                    .iter()
-                    .for_each(|((lhs_gd, rhs_gd), out_gd)| gradients::$method(lhs_gd, rhs_gd, out_gd))
-            })));
-
-            let op = String::from(stringify!($method));
-            let children = if Rc::ptr_eq(&lhs.grad_data, &rhs.grad_data) {
-                vec![lhs.clone()]
-            } else {
-                vec![lhs.clone(), rhs.clone()]
-            };
-
-            Value { grad_data, children, backward_fn, op }
+macro_rules! custom_binary_operator_impl {
+    (use $fn_name: ident for $type_: ident { $(#[$meta:meta])* fn $method: ident as $variant: ident }) => {
+        impl $type_ {
+            $(#[$meta])*
+            pub fn $method(&self, other: &$type_) -> $type_ {
+                Value::new_binary(
+                    self,
+                    other,
+                    Op::$variant,
+                    scalars::$fn_name,
+                    gradients::$fn_name,
+                    derivatives::$fn_name,
+                )
+            }
         }
+    };
+}
 
-        fn $method_rev(lhs: &$type_, rhs: &$type_) -> $type_ {
-            let mut value = lhs.$reverse_val(rhs.clone().$reverse_arg(-1.));
-            value.op = String::from(stringify!($method_rev));
-            value
+macro_rules! binary_operator_impl {
+    (@node fn $method: ident as $variant: ident for $type_: ident) => {
+        fn $method(lhs: &$type_, rhs: &$type_) -> $type_ {
+            $type_::new_binary(
+                lhs,
+                rhs,
+                Op::$variant,
+                |lhs, rhs| lhs.$method(rhs),
+                gradients::$method,
+                derivatives::$method,
+            )
         }
+    };
+    (impl $op:tt for $type_: ident with fn $method: ident as $variant: ident and reverse $op_rev:tt fn $method_rev: ident as $variant_rev: ident) => {
+        binary_operator_impl! { @node fn $method as $variant for $type_ }
+        binary_operator_impl! { @node fn $method_rev as $variant_rev for $type_ }
 
         impl_op! { $op |a: &Value, b: &Value| -> Value { $method(a, b) } }
         impl_op_commutative! { $op |a: Value, b: &Value| -> Value { $method(&a, b) } }
         impl_op! { $op |a: Value, b: Value| -> Value { $method(&a, &b) } }
-        impl_op_commutative! { $op |a: &Value, b: f64| -> Value { $method(a, &Value::new(b))  } }
-        impl_op_commutative! { $op |a: Value, b: f64| -> Value { &a $op b } }
+        impl_op_commutative! { $op |a: &Value, b: Float| -> Value { $method(a, &Value::constant(b))  } }
+        impl_op_commutative! { $op |a: Value, b: Float| -> Value { &a $op b } }
 
         impl_op! { $op_rev |a: &Value, b: &Value| -> Value { $method_rev(a, b) } }
         impl_op! { $op_rev |a: Value, b: &Value| -> Value { $method_rev(&a, b) } }
+        impl_op! { $op_rev |a: &Value, b: Value| -> Value { $method_rev(a, &b) } }
         impl_op! { $op_rev |a: Value, b: Value| -> Value { $method_rev(&a, &b) } }
-        impl_op! { $op_rev |a: &Value, b: f64| -> Value { $method_rev(a, &Value::new(b))  } }
-        impl_op! { $op_rev |a: Value, b: f64| -> Value { &a $op_rev b } }
-        impl_op! { $op_rev |a: f64, b: &Value| -> Value { $method_rev(&Value::new(a), b)  } }
-        impl_op! { $op_rev |a: f64, b: Value| -> Value { a $op_rev &b } }
+        impl_op! { $op_rev |a: &Value, b: Float| -> Value { $method_rev(a, &Value::constant(b))  } }
+        impl_op! { $op_rev |a: Value, b: Float| -> Value { &a $op_rev b } }
+        impl_op! { $op_rev |a: Float, b: &Value| -> Value { $method_rev(&Value::constant(a), b)  } }
+        impl_op! { $op_rev |a: Float, b: Value| -> Value { a $op_rev &b } }
+    };
+}
+
+macro_rules! assign_operator_impl {
+    ($op_assign:tt by $op:tt) => {
+        impl_op! { $op_assign |a: &mut Value, b: &Value| { *a = &*a $op b } }
+        impl_op! { $op_assign |a: &mut Value, b: Value| { *a = &*a $op &b } }
+        impl_op! { $op_assign |a: &mut Value, b: Float| { *a = &*a $op b } }
     };
 }
 
 // NOTE assumption: main operator is commutative, reverse - is not
-binary_operator_impl! { impl + for Value with fn add and reverse - fn sub by (add, mul) }
-binary_operator_impl! { impl * for Value with fn mul and reverse / fn div by (mul, pow) }
-custom_operator_impl! { use powf for Value { fn pow with rhs: f64 } }
-custom_operator_impl! { use relu for Value { fn relu } }
+binary_operator_impl! { impl + for Value with fn add as Add and reverse - fn sub as Sub }
+binary_operator_impl! { impl * for Value with fn mul as Mul and reverse / fn div as Div }
+assign_operator_impl! { += by + }
+assign_operator_impl! { -= by - }
+assign_operator_impl! { *= by * }
+assign_operator_impl! { /= by / }
+
+fn neg(value: &Value) -> Value {
+    let mut value = value * -1.;
+    value.op = Op::Neg;
+    value
+}
+
+impl_op! { - |a: &Value| -> Value { neg(a) } }
+impl_op! { - |a: Value| -> Value { neg(&a) } }
+
+custom_operator_impl! { use powf for Value { fn pow as Pow with exp: Float } }
+custom_operator_impl! { use relu for Value { fn relu as ReLU } }
+custom_operator_impl! { use tanh for Value { fn tanh as Tanh } }
+custom_operator_impl! { use sigmoid for Value { fn sigmoid as Sigmoid } }
+custom_operator_impl! { use exp for Value { fn exp as Exp } }
+custom_operator_impl! { use ln for Value {
+    /// Returns natural logarithm. Non-positive values produce NaN.
+    fn ln as Ln
+} }
+custom_operator_impl! { use log for Value {
+    /// Returns logarithm with respect to given base. Non-positive values produce NaN.
+    fn log as Log with base: Float
+} }
+custom_operator_impl! { use sqrt for Value {
+    /// Returns square root with gradient computed as `0.5 / (sqrt(x) + eps)`.
+    fn sqrt_eps as SqrtEps with eps: Float
+} }
+custom_operator_impl! { use abs for Value {
+    /// Returns absolute value. Uses `sign(x)` as subgradient, so it is zero at zero.
+    fn abs as Abs
+} }
+custom_operator_impl! { use log2 for Value {
+    /// Returns base 2 logarithm. Non-positive values produce NaN.
+    fn log2 as Log2
+} }
+custom_operator_impl! { use log10 for Value {
+    /// Returns base 10 logarithm. Non-positive values produce NaN.
+    fn log10 as Log10
+} }
+custom_operator_impl! { use sin for Value { fn sin as Sin } }
+custom_operator_impl! { use cos for Value { fn cos as Cos } }
+custom_operator_impl! { use tan for Value { fn tan as Tan } }
+custom_operator_impl! { use atan for Value { fn atan as Atan } }
+custom_operator_impl! { use sinh for Value { fn sinh as Sinh } }
+custom_operator_impl! { use cosh for Value { fn cosh as Cosh } }
+custom_operator_impl! { use elu for Value {
+    /// Returns exponential linear unit: `x` for positive values and `alpha * (exp(x) - 1)` otherwise.
+    fn elu as Elu with alpha: Float
+} }
+custom_operator_impl! { use silu for Value {
+    /// Returns SiLU (swish) activation: `x * sigmoid(x)`.
+    fn silu as SiLU
+} }
+custom_operator_impl! { use softplus for Value {
+    /// Returns softplus activation: `ln(1 + exp(x))`.
+    fn softplus as Softplus
+} }
+custom_operator_impl! { use clamp for Value {
+    /// Restricts value to `[lo, hi]` interval. Gradient is passed only when value is inside the interval.
+    fn clamp as Clamp with lo: Float, hi: Float
+} }
+custom_operator_impl! { use sign for Value {
+    /// Returns -1, 0 or 1 depending on sign of the value. Contributes zero gradient.
+    fn sign as Sign
+} }
+custom_operator_impl! { use erf for Value {
+    /// Returns Gauss error function.
+    fn erf as Erf
+} }
+custom_operator_impl! { use powi for Value {
+    /// Raises value to an integer power.
+    fn powi as Powi with exp: i32
+} }
+custom_operator_impl! { use recip for Value {
+    /// Returns reciprocal value: `1 / x`.
+    fn recip as Recip
+} }
+custom_operator_impl! { use expm1 for Value {
+    /// Returns `exp(x) - 1` computed accurately for values close to zero.
+    fn expm1 as Expm1
+} }
+custom_operator_impl! { use ln_1p for Value {
+    /// Returns `ln(1 + x)` computed accurately for values close to zero. Values not greater than -1 produce NaN.
+    fn ln_1p as Ln1p
+} }
+custom_operator_impl! { use floor for Value {
+    /// Returns the largest integer less than or equal to value. Contributes zero gradient.
+    fn floor as Floor
+} }
+custom_operator_impl! { use ceil for Value {
+    /// Returns the smallest integer greater than or equal to value. Contributes zero gradient.
+    fn ceil as Ceil
+} }
+custom_operator_impl! { use round for Value {
+    /// Returns the nearest integer, rounding half-way cases away from zero. Contributes zero gradient.
+    fn round as Round
+} }
+custom_operator_impl! { use sign_ste for Value {
+    /// Returns sign of the value like `sign`, but passes gradient through unchanged (straight-through estimator).
+    fn sign_ste as SignSte
+} }
+custom_operator_impl! { use round_ste for Value {
+    /// Returns rounded value like `round`, but passes gradient through unchanged (straight-through estimator).
+    fn round_ste as RoundSte
+} }
+custom_operator_impl! { use safe_ln for Value {
+    /// Returns `ln(max(x, eps))`. Gradient is computed as `1 / max(x, eps)`, so it never becomes infinite or NaN.
+    fn safe_ln as SafeLn with eps: Float
+} }
+custom_operator_impl! { use softsign for Value {
+    /// Returns softsign activation: `x / (1 + |x|)`.
+    fn softsign as Softsign
+} }
+custom_operator_impl! { use hard_tanh for Value {
+    /// Returns piecewise linear approximation of tanh: value clamped to `[-1, 1]`.
+    fn hard_tanh as HardTanh
+} }
+custom_operator_impl! { use hard_sigmoid for Value {
+    /// Returns piecewise linear approximation of sigmoid: `x / 6 + 0.5` clamped to `[0, 1]`.
+    fn hard_sigmoid as HardSigmoid
+} }
+custom_binary_operator_impl! { use max for Value {
+    /// Returns maximum of two values. Gradient goes to the greater one and is split equally on ties.
+    fn max as Max
+} }
+custom_binary_operator_impl! { use min for Value {
+    /// Returns minimum of two values. Gradient goes to the smaller one and is split equally on ties.
+    fn min as Min
+} }
+custom_binary_operator_impl! { use pow for Value {
+    /// Raises value to a power specified by another value, so gradient flows into both base and exponent.
+    fn pow_value as PowValue
+} }
+custom_binary_operator_impl! { use atan2 for Value {
+    /// Returns four quadrant arctangent of `self` (y) and `other` (x).
+    fn atan2 as Atan2
+} }
+
+impl Drop for Value {
+    fn drop(&mut self) {
+        // release exclusively owned descendants iteratively: recursive drop overflows the stack on deep graphs
+        let mut stack = Shared::get_mut(&mut self.children).map(std::mem::take).unwrap_or_default();
+        while let Some(mut value) = stack.pop() {
+            if let Some(children) = Shared::get_mut(&mut value.children) {
+                stack.append(children);
+            }
+        }
+    }
+}
+
+/// A map keyed by node identity: an explicit alternative to using `Value` as a key of `HashMap`.
+#[derive(Clone, Debug)]
+pub struct IdMap<T> {
+    items: HashMap<NonZeroUsize, T>,
+}
+
+impl<T> Default for IdMap<T> {
+    fn default() -> Self {
+        Self { items: HashMap::default() }
+    }
+}
+
+impl<T> IdMap<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, key: &Value, item: T) -> Option<T> {
+        self.items.insert(key.id(), item)
+    }
+
+    pub fn get(&self, key: &Value) -> Option<&T> {
+        self.items.get(&key.id())
+    }
+
+    pub fn get_mut(&mut self, key: &Value) -> Option<&mut T> {
+        self.items.get_mut(&key.id())
+    }
+
+    pub fn remove(&mut self, key: &Value) -> Option<T> {
+        self.items.remove(&key.id())
+    }
+
+    pub fn contains(&self, key: &Value) -> bool {
+        self.items.contains_key(&key.id())
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Iterates over identifiers of nodes and items.
+    pub fn iter(&self) -> impl Iterator<Item = (NonZeroUsize, &T)> {
+        self.items.iter().map(|(id, item)| (*id, item))
+    }
+}
 
 impl Hash for Value {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        (self.grad_data.as_ref() as *const RefCell<GradientData>).hash(state)
+        self.id().hash(state)
     }
 }
 
 impl PartialEq<Self> for Value {
     fn eq(&self, other: &Self) -> bool {
-        Rc::ptr_eq(&self.grad_data, &other.grad_data)
+        Shared::ptr_eq(&self.grad_data, &other.grad_data)
     }
 }
 
 impl Eq for Value {}
 
+/// Orders values by their data following `Float` semantics: only comparison with NaN returns `None`.
+/// NOTE ordering is defined by data while equality is defined by identity, so two different nodes with
+/// the same data are ordered as `Equal` (`a <= b` and `a >= b` hold), but `a != b`.
+impl PartialOrd<Self> for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.get_data().partial_cmp(&other.get_data())
+    }
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_fmt(format_args!("Value[data={}, grad={}]", self.get_data(), self.get_grad()))
+        match self.grad_data.borrow().label.as_ref() {
+            Some(label) => {
+                f.write_fmt(format_args!("Value[label={}, data={}, grad={}]", label, self.get_data(), self.get_grad()))
+            }
+            None => f.write_fmt(format_args!("Value[data={}, grad={}]", self.get_data(), self.get_grad())),
+        }
     }
 }
 
@@ -227,8 +2287,32 @@ impl Debug for Value {
     }
 }
 
+impl From<Float> for Value {
+    fn from(data: Float) -> Self {
+        Value::new(data)
+    }
+}
+
 impl Sum for Value {
     fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
-        iter.fold(Value::new(0.), |acc, v| acc + v)
+        super::sum(iter.collect::<Vec<_>>().as_slice())
+    }
+}
+
+impl<'a> Sum<&'a Value> for Value {
+    fn sum<I: Iterator<Item = &'a Value>>(iter: I) -> Self {
+        iter.cloned().sum()
+    }
+}
+
+impl Product for Value {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        super::product(iter.collect::<Vec<_>>().as_slice())
+    }
+}
+
+impl<'a> Product<&'a Value> for Value {
+    fn product<I: Iterator<Item = &'a Value>>(iter: I) -> Self {
+        iter.cloned().product()
     }
 }
@@ -0,0 +1,35 @@
+#![cfg(feature = "f32")]
+
+use mikrograd::single::*;
+
+#[test]
+fn can_use_single_precision() {
+    let x = new_value(0.5_f32);
+    let y = (&x * &x).tanh() + x.exp();
+    y.backward();
+
+    let expected: f32 = 2. * 0.5 * (1. - 0.25_f32.tanh().powi(2)) + 0.5_f32.exp();
+    assert_eq!(std::mem::size_of::<Float>(), 4);
+    assert!((y.get_data() - (0.25_f32.tanh() + 0.5_f32.exp())).abs() < 1E-6);
+    assert!((x.get_grad() - expected).abs() < 1E-6);
+}
+
+#[test]
+fn can_train_model_with_single_precision() {
+    let mut model =
+        MLP::builder().input(2).hidden(4, NeuronType::Tanh).output(1).init(Init::Xavier).seed(7).build().unwrap();
+    let inputs = [new_value(1.), new_value(-1.)];
+    let loss = |model: &MLP| (&model.call(&inputs)[0] - 0.5).powi(2);
+
+    let loss_before = loss(&model).get_data();
+    (0..50).for_each(|_| {
+        let loss = loss(&model);
+        model.zero_grad();
+        loss.backward();
+        model.parameters_mut().for_each(|p| p.set_data(p.get_data() - 0.1 * p.get_grad()));
+    });
+    let loss_after = loss(&model).get_data();
+
+    assert!(loss_before > 1E-3);
+    assert!(loss_after < 1E-4);
+}
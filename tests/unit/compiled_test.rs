@@ -0,0 +1,34 @@
+use super::super::new_value;
+use super::*;
+
+fn build(x: &Value, y: &Value, w: &Value) -> Value {
+    (x * y + x.sin() * w).tanh() + x.powi(2)
+}
+
+#[test]
+fn can_evaluate_and_differentiate_compiled_graph() {
+    let (x, y, w) = (new_value(0.5), new_value(-1.), new_value(2.));
+    let mut compiled = build(&x, &y, &w).compile(&[x.clone(), y.clone()]);
+
+    [(0.5, -1.), (1.5, 2.), (-0.3, 0.7)].into_iter().for_each(|(x_data, y_data)| {
+        let (x, y) = (new_value(x_data), new_value(y_data));
+        let expected = build(&x, &y, &w);
+        expected.backward();
+
+        assert!((compiled.eval(&[x_data, y_data]) - expected.get_data()).abs() < 1E-12);
+        let grads = compiled.grad(&[x_data, y_data]);
+        assert!((grads[0] - x.get_grad()).abs() < 1E-12);
+        assert!((grads[1] - y.get_grad()).abs() < 1E-12);
+    });
+
+    assert_eq!((x.get_data(), x.get_grad()), (0.5, 0.));
+}
+
+#[test]
+fn can_compile_graph_with_shared_operands() {
+    let (x, z) = (new_value(3.), new_value(1.));
+    let mut compiled = (&x * &x).compile(&[x.clone(), z]);
+
+    assert_eq!(compiled.grad(&[4., 1.]), vec![8., 0.]);
+    assert_eq!(compiled.to_string(), "%1 = mul(%0)\n");
+}
@@ -0,0 +1,50 @@
+use super::super::Value;
+use super::*;
+
+#[test]
+fn can_propagate_tangents_through_arithmetic() {
+    let x = DualValue::variable(3.);
+
+    let result = x * x + 2. * x - 1. / x + (x / 2. - 1.);
+
+    assert_eq!(result.get_data(), 9. + 6. - 1. / 3. + 0.5);
+    assert_eq!(result.get_tangent(), 6. + 2. + 1. / 9. + 0.5);
+}
+
+#[test]
+fn can_calculate_partial_derivatives() {
+    let f = |x: DualValue, y: DualValue| x * y + (x / y).sin();
+
+    let dx = f(DualValue::variable(2.), DualValue::constant(3.));
+    let dy = f(DualValue::constant(2.), DualValue::variable(3.));
+
+    assert_eq!(dx.get_tangent(), 3. + (2_f64 / 3.).cos() / 3.);
+    assert_eq!(dy.get_tangent(), 2. - (2_f64 / 3.).cos() * 2. / 9.);
+}
+
+#[test]
+fn can_cross_check_reverse_mode_gradients() {
+    let data = 0.4;
+
+    let dual = DualValue::variable(data);
+    let dual = (dual.tanh() + dual.sigmoid() * dual.exp() - dual.ln() + dual.sqrt() + dual.pow(1.5)).relu()
+        + dual.abs()
+        + dual.cos() * dual.sin()
+        + dual.tan()
+        + dual.atan()
+        + dual.sinh() / dual.cosh()
+        + dual.powi(3);
+
+    let x = Value::new(data);
+    let value = (x.tanh() + x.sigmoid() * x.exp() - x.ln() + x.sqrt() + x.pow(1.5)).relu()
+        + x.abs()
+        + x.cos() * x.sin()
+        + x.tan()
+        + x.atan()
+        + x.sinh() / x.cosh()
+        + x.powi(3);
+    value.backward();
+
+    assert!((dual.get_data() - value.get_data()).abs() < 1E-12);
+    assert!((dual.get_tangent() - x.get_grad()).abs() < 1E-9);
+}
@@ -0,0 +1,141 @@
+use super::*;
+
+fn create_values(data: &[f64]) -> Vec<Value> {
+    data.iter().cloned().map(Value::new).collect()
+}
+
+#[test]
+fn can_calculate_softmax() {
+    let values = create_values(&[1., 2., 3.]);
+
+    let probs = softmax(values.as_slice());
+
+    let total = 1_f64.exp() + 2_f64.exp() + 3_f64.exp();
+    probs.iter().zip([1_f64, 2., 3.]).for_each(|(prob, x)| assert!((prob.get_data() - x.exp() / total).abs() < 1E-12));
+    // values are not scaled by unit temperature
+    assert_eq!(probs[0].children[0].children[0], values[0]);
+
+    probs[0].backward();
+    let (p0, p1) = (probs[0].get_data(), probs[1].get_data());
+    assert!((values[0].get_grad() - p0 * (1. - p0)).abs() < 1E-12);
+    assert!((values[1].get_grad() + p0 * p1).abs() < 1E-12);
+}
+
+#[test]
+fn can_calculate_softmax_with_temperature() {
+    let values = create_values(&[1000., 1001.]);
+
+    let sharp = softmax(values.as_slice());
+    let smooth = softmax_with_temperature(values.as_slice(), 10.);
+
+    assert!(sharp.iter().all(|prob| prob.get_data().is_finite()));
+    assert!((sharp.iter().map(|p| p.get_data()).sum::<f64>() - 1.).abs() < 1E-12);
+    assert!(smooth[1].get_data() < sharp[1].get_data());
+    assert!((smooth[1].get_data() - 1. / (1. + (-0.1_f64).exp())).abs() < 1E-12);
+}
+
+fn depth(value: &Value) -> usize {
+    1 + value.children.iter().map(depth).max().unwrap_or(0)
+}
+
+#[test]
+fn can_sum_values_with_balanced_tree() {
+    let values = create_values(&[1.; 1024]);
+
+    let result = sum(values.as_slice());
+    assert_eq!(result.get_data(), 1024.);
+    assert_eq!(depth(&result), 11);
+
+    result.backward();
+    assert!(values.iter().all(|value| value.get_grad() == 1.));
+
+    assert_eq!(sum(&[]).get_data(), 0.);
+    assert_eq!(values.into_iter().sum::<Value>().get_data(), 1024.);
+}
+
+#[test]
+fn can_multiply_values_with_balanced_tree() {
+    let values = create_values(&[1., 2., 3., 4.]);
+
+    let result = product(values.as_slice());
+    assert_eq!(result.get_data(), 24.);
+    assert_eq!(depth(&result), 3);
+
+    result.backward();
+    assert_eq!(values.iter().map(|v| v.get_grad()).collect::<Vec<_>>(), vec![24., 12., 8., 6.]);
+
+    assert_eq!(product(&[]).get_data(), 1.);
+}
+
+#[test]
+fn can_calculate_mean() {
+    let values = create_values(&[1., 2., 3., 6.]);
+
+    let result = mean(values.as_slice());
+    assert_eq!(result.get_data(), 3.);
+
+    result.backward();
+    assert!(values.iter().all(|value| value.get_grad() == 0.25));
+}
+
+#[test]
+fn can_calculate_dot_product() {
+    let (lhs, rhs) = (create_values(&[1., 2., 3.]), create_values(&[4., 5., 6.]));
+
+    let result = dot(lhs.as_slice(), rhs.as_slice());
+    assert_eq!(result.get_data(), 32.);
+
+    result.backward();
+    assert_eq!(lhs.iter().map(|v| v.get_grad()).collect::<Vec<_>>(), vec![4., 5., 6.]);
+    assert_eq!(rhs.iter().map(|v| v.get_grad()).collect::<Vec<_>>(), vec![1., 2., 3.]);
+}
+
+#[test]
+fn can_calculate_norm() {
+    let values = create_values(&[3., -4.]);
+
+    let result = norm(values.as_slice());
+    assert_eq!(result.get_data(), 5.);
+
+    result.backward();
+    assert!((values[0].get_grad() - 0.6).abs() < 1E-9);
+    assert!((values[1].get_grad() + 0.8).abs() < 1E-9);
+
+    let zeros = create_values(&[0., 0.]);
+    norm(zeros.as_slice()).backward();
+    assert!(zeros.iter().all(|value| value.get_grad().is_finite()));
+}
+
+#[test]
+fn can_find_argmax_and_argmin() {
+    let values = create_values(&[1., 5., -2., 5., -2.]);
+
+    let (idx, value) = argmax(values.as_slice()).unwrap();
+    assert_eq!(idx, 1);
+    assert_eq!(value, &values[1]);
+
+    let (idx, value) = argmin(values.as_slice()).unwrap();
+    assert_eq!(idx, 2);
+    assert_eq!(value.get_data(), -2.);
+
+    assert!(argmax(&[]).is_none());
+    assert!(argmin(&[]).is_none());
+}
+
+#[test]
+fn can_calculate_jacobian() {
+    let result = jacobian(|x| vec![&x[0] * &x[1], x[0].sin() + x[1].powi(2), Value::new(1.)], &[2., 3.]);
+
+    assert_eq!(result, vec![vec![3., 2.], vec![2_f64.cos(), 6.], vec![0., 0.]]);
+}
+
+#[test]
+fn can_calculate_hessian() {
+    let result = hessian(|x| x[0].powi(3) * &x[1] + x[1].exp(), &[2., 1.]);
+
+    assert_eq!(result.len(), 2);
+    assert!((result[0][0] - 12.).abs() < 1E-12);
+    assert!((result[0][1] - 12.).abs() < 1E-12);
+    assert!((result[1][0] - 12.).abs() < 1E-12);
+    assert!((result[1][1] - 1_f64.exp()).abs() < 1E-12);
+}
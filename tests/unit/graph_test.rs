@@ -0,0 +1,110 @@
+use super::super::checkpoint;
+use super::*;
+
+fn create_graph() -> (Value, Value, Value) {
+    let x = Value::new(2.).with_label("x");
+    let y = Value::new(-3.).with_label("y");
+    let z =
+        ((&x * &y).tanh() + x.pow(2.) - y.clamp(-1., 1.) + Value::logsumexp(&[x.clone(), y.clone()])).with_label("z");
+
+    (x, y, z)
+}
+
+#[test]
+fn can_create_graph_from_value() {
+    let (x, _, z) = create_graph();
+    z.backward();
+
+    let graph = Graph::from_value(&z);
+
+    assert_eq!(graph.nodes.len(), 10);
+    let root = graph.nodes.last().unwrap();
+    assert_eq!((root.op.clone(), root.data, root.grad), (Op::Add, z.get_data(), 1.));
+    assert_eq!(root.label.as_deref(), Some("z"));
+    let leaf = graph.nodes.iter().find(|node| node.label.as_deref() == Some("x")).unwrap();
+    assert_eq!((leaf.op.clone(), leaf.data, leaf.grad), (Op::Leaf, 2., x.get_grad()));
+    assert!(graph.nodes.iter().enumerate().all(|(idx, node)| node.children.iter().all(|&child| child < idx)));
+}
+
+#[test]
+fn can_render_graph() {
+    let x = Value::new(2.).with_label("x");
+    let y = (&x * &x).with_label("\"y\"");
+    y.backward();
+
+    assert_eq!(
+        y.to_mermaid(),
+        "flowchart LR\n    n0[\"x | data 2.0000 | grad 4.0000\"]\n    \
+         n1[\"#quot;y#quot; = mul | data 4.0000 | grad 1.0000\"]\n    n0 --> n1\n"
+    );
+    assert_eq!(
+        y.to_dot(),
+        "digraph {\n    rankdir=LR;\n    n0 [shape=box, label=\"x | data 2.0000 | grad 4.0000\"];\n    \
+         n1 [shape=box, label=\"\\\"y\\\" = mul | data 4.0000 | grad 1.0000\"];\n    n0 -> n1;\n}\n"
+    );
+}
+
+#[test]
+fn can_calculate_graph_stats() {
+    let (_, _, z) = create_graph();
+
+    let stats = z.graph_stats();
+
+    assert_eq!((stats.nodes, stats.depth, stats.leaves), (10, 6, 2));
+    assert_eq!(stats.ops.get("add"), Some(&2));
+    assert_eq!(stats.ops.get("leaf"), Some(&2));
+    assert_eq!(stats.ops.values().sum::<usize>(), 10);
+    assert_eq!(Value::new(1.).graph_stats().depth, 1);
+}
+
+#[test]
+fn can_rebuild_value_from_graph() {
+    let (_, _, z) = create_graph();
+    z.backward();
+    let graph = Graph::from_value(&z);
+
+    let value = graph.to_value().unwrap();
+
+    assert_eq!(Graph::from_value(&value), graph);
+    value.backward();
+    let leaf = value.topological_order()[0];
+    assert_eq!(leaf.get_label().as_deref(), Some("x"));
+    assert_eq!(leaf.get_grad(), 2. * graph.nodes[0].grad);
+}
+
+#[test]
+fn cannot_rebuild_invalid_graph() {
+    let x = Value::new(1.);
+    let value = checkpoint(std::slice::from_ref(&x), |inputs| inputs[0].exp());
+    let expected = "cannot rebuild checkpoint node 1: its function is unknown";
+    assert_eq!(Graph::from_value(&value).to_value().err(), Some(expected.to_string()));
+
+    let value = x.custom_unary(|x| x * 2., |_, _| 2., "double");
+    let expected = "cannot rebuild custom 'double' node 1: its function is unknown";
+    assert_eq!(Graph::from_value(&value).to_value().err(), Some(expected.to_string()));
+
+    let value = super::super::no_grad(|| x.exp());
+    let expected = "cannot rebuild 'exp' node 0: it has no children (recorded without grad)";
+    assert_eq!(Graph::from_value(&value).to_value().err(), Some(expected.to_string()));
+
+    let mut graph = Graph::from_value(&x.exp());
+    graph.nodes[1].children[0] = 5;
+    assert_eq!(graph.to_value().err(), Some("node 1 has invalid child 5".to_string()));
+
+    assert_eq!(Graph { nodes: vec![] }.to_value().err(), Some("graph is empty".to_string()));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn can_serialize_graph_as_json() {
+    let (_, _, z) = create_graph();
+    z.backward();
+    let graph = Graph::from_value(&z);
+
+    let json = graph.to_json().unwrap();
+    let restored = Graph::from_json(json.as_str()).unwrap();
+
+    assert!(json.contains(r#""op":{"Pow":{"exp":2.0}}"#));
+    assert_eq!(restored, graph);
+    assert_eq!(restored.to_value().unwrap().get_data(), z.get_data());
+}
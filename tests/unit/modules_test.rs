@@ -9,6 +9,69 @@ fn can_create_neuron() {
     assert!(matches!(neuron.ntype, NeuronType::ReLU));
 }
 
+#[test]
+fn can_create_neuron_without_bias() {
+    let neuron = Neuron::new(2, NeuronType::Linear).with_bias(false);
+    let (w0, w1) = (neuron.w[0].get_data(), neuron.w[1].get_data());
+
+    let result = neuron.call(&[Value::new(2.), Value::new(3.)]);
+
+    assert_eq!(neuron.parameters().count(), 2);
+    assert!((result.get_data() - (2. * w0 + 3. * w1)).abs() < 1E-12);
+    assert_eq!(neuron.with_bias(true).parameters().count(), 3);
+    assert_eq!(Layer::new(3, 4, NeuronType::ReLU).with_bias(false).parameters().count(), 12);
+}
+
+#[test]
+fn can_initialize_parameters() {
+    let weights =
+        |layer: &Layer| layer.neurons.iter().flat_map(|n| n.w.iter().map(|w| w.get_data())).collect::<Vec<_>>();
+    let biases =
+        |layer: &Layer| layer.neurons.iter().filter_map(|n| n.b.as_ref().map(|b| b.get_data())).collect::<Vec<_>>();
+
+    let layer = Layer::new(50, 40, NeuronType::ReLU).with_init(Init::Zeros);
+    assert!(weights(&layer).iter().all(|&w| w == 0.));
+
+    let layer = layer.with_init(Init::Uniform { low: 2., high: 3. });
+    assert!(weights(&layer).iter().all(|&w| (2. ..3.).contains(&w)));
+
+    let limit = (6. / 90_f64).sqrt();
+    let layer = layer.with_init(Init::Xavier);
+    assert!(weights(&layer).iter().all(|&w| w.abs() <= limit));
+
+    let mean_std = |values: &[f64]| {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        (mean, (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64).sqrt())
+    };
+    let (mean, std) = mean_std(weights(&layer.with_init(Init::Normal { mean: 5., std: 2. })).as_slice());
+    assert!((mean - 5.).abs() < 0.2 && (std - 2.).abs() < 0.2);
+
+    let layer = Layer::new(50, 40, NeuronType::ReLU).with_init(Init::He);
+    let (mean, std) = mean_std(weights(&layer).as_slice());
+    assert!(mean.abs() < 0.02 && (std - 0.2).abs() < 0.02);
+    assert!(biases(&layer).iter().all(|&b| b == 0.));
+}
+
+#[test]
+fn can_create_modules_with_seeded_rng() {
+    use rand::SeedableRng;
+    let data = |mlp: &MLP| mlp.parameters().map(|p| p.get_data()).collect::<Vec<_>>();
+    let create = |seed| MLP::new_with_rng(2, &[4, 1], NeuronType::Tanh, &mut rand::rngs::StdRng::seed_from_u64(seed));
+
+    assert_eq!(data(&create(7)), data(&create(7)));
+    assert_ne!(data(&create(7)), data(&create(8)));
+    assert_eq!(
+        data(&super::super::new_mlp_with_seed(2, &[3, 1], 1)),
+        data(&super::super::new_mlp_with_seed(2, &[3, 1], 1))
+    );
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+    let (lhs, rhs) = (create(1).with_init_rng(Init::He, &mut rng), create(2).with_init_rng(Init::He, &mut rng));
+    assert_ne!(data(&lhs), data(&rhs));
+    let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+    assert_eq!(data(&lhs), data(&create(5).with_init_rng(Init::He, &mut rng)));
+}
+
 #[test]
 fn can_create_layer() {
     let layer = Layer::new(3, 4, NeuronType::Linear);
@@ -19,18 +82,686 @@ fn can_create_layer() {
 
 #[test]
 fn can_create_mlp() {
-    let mlp = MLP::new(2, &[16, 16, 1]);
+    let mlp = MLP::new(2, &[16, 16, 1], NeuronType::ReLU);
 
     assert_eq!(mlp.layers.len(), 2 + 1);
     assert_eq!(mlp.parameters().count(), 337);
 }
 
+#[test]
+fn can_build_mlp() {
+    let build = |seed| {
+        MLP::builder()
+            .input(2)
+            .hidden(4, NeuronType::ReLU)
+            .hidden(3, NeuronType::Tanh)
+            .output(1)
+            .init(Init::Xavier)
+            .bias(false)
+            .seed(seed)
+            .build()
+            .unwrap()
+    };
+    let data = |mlp: &MLP| mlp.parameters().map(|p| p.get_data()).collect::<Vec<_>>();
+
+    let mlp = build(42);
+    assert_eq!(mlp.parameters().count(), 2 * 4 + 4 * 3 + 3);
+    assert_eq!(mlp.to_string().matches("TanhNeuron(4)").count(), 3);
+    assert!(matches!(mlp.layers[2].neurons[0].ntype, NeuronType::Linear));
+    assert_eq!(data(&mlp), data(&build(42)));
+    assert_ne!(data(&mlp), data(&build(43)));
+
+    assert_eq!(MLP::builder().input(2).output(1).build().unwrap().parameters().count(), 3);
+    assert!(MLP::builder().output(1).build().is_err());
+
+    // weights are sampled once using given strategy
+    let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(42);
+    let expected = (0..2).map(|_| Init::Xavier.sample(&mut rng, 2, 1)).collect::<Vec<_>>();
+    let mlp = MLP::builder().input(2).output(1).init(Init::Xavier).bias(false).seed(42).build().unwrap();
+    assert_eq!(data(&mlp), expected);
+}
+
+#[test]
+fn can_get_named_parameters() {
+    let mlp = MLP::builder().input(2).hidden(3, NeuronType::ReLU).output(1).build().unwrap();
+
+    let named = mlp.named_parameters().collect::<Vec<_>>();
+    assert_eq!(named.len(), mlp.parameters().count());
+    assert_eq!(named[0].0, "layers.0.neurons.0.w.0");
+    assert_eq!(named[2].0, "layers.0.neurons.0.b");
+    assert_eq!(named.last().unwrap().0, "layers.1.neurons.0.b");
+    assert!(named.iter().zip(mlp.parameters()).all(|((_, named), p)| named.id() == p.id()));
+
+    let model = Sequential::new().with(Dropout::new(0.5)).with(Residual::new(Layer::new(2, 1, NeuronType::Tanh), 2, 1));
+    let names = model.named_parameters().map(|(name, _)| name).collect::<Vec<_>>();
+    assert_eq!(names.len(), model.parameters().count());
+    assert_eq!(names[0], "modules.1.inner.neurons.0.w.0");
+    assert_eq!(names.last().unwrap(), "modules.1.projection.neurons.0.w.1");
+}
+
+#[test]
+fn can_get_param_groups() {
+    let mlp = MLP::builder().input(2).hidden(3, NeuronType::ReLU).output(1).build().unwrap();
+
+    let groups = mlp.param_groups();
+    assert_eq!(
+        groups.iter().map(|g| (g.name.as_str(), g.parameters.len())).collect::<Vec<_>>(),
+        [("layers.0", 9), ("layers.1", 4)]
+    );
+
+    let mut groups = mlp.weight_bias_groups();
+    groups[1] = groups[1].clone().with_learning_rate(0.5).with_weight_decay(0.);
+    assert_eq!((groups[0].parameters.len(), groups[1].parameters.len()), (9, 4));
+    assert_eq!((groups[0].learning_rate, groups[1].learning_rate, groups[1].weight_decay), (None, Some(0.5), Some(0.)));
+
+    groups[1].parameters.iter_mut().for_each(|p| p.set_data(42.));
+    assert!(mlp.layers.iter().flat_map(|l| l.neurons.iter()).all(|n| n.b.as_ref().unwrap().get_data() == 42.));
+    assert!(mlp.layers[0].neurons[0].w.iter().all(|w| w.get_data() != 42.));
+}
+
+#[test]
+fn can_save_and_load_state_dict() {
+    let source = MLP::builder().input(2).hidden(3, NeuronType::ReLU).output(1).seed(1).build().unwrap();
+    let mut target = MLP::builder().input(2).hidden(3, NeuronType::ReLU).output(1).seed(2).build().unwrap();
+
+    let state = source.state_dict();
+    assert_eq!(state.len(), 13);
+    assert_eq!(state[0], ("layers.0.neurons.0.w.0".to_string(), source.layers[0].neurons[0].w[0].get_data()));
+
+    target.load_state_dict(&state).unwrap();
+    assert_eq!(target.state_dict(), state);
+
+    let mut other = MLP::builder().input(2).hidden(4, NeuronType::ReLU).output(1).build().unwrap();
+    let other_state = other.state_dict();
+    assert_eq!(other.load_state_dict(&state), Err("expected 17 parameters, got 13".to_string()));
+    let mut renamed = state.clone();
+    renamed[12].0 = "layers.1.neurons.0.w.3".to_string();
+    assert!(target.load_state_dict(&renamed).unwrap_err().contains("'layers.1.neurons.0.b'"));
+    assert_eq!(other.state_dict(), other_state);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn can_serialize_mlp_as_json() {
+    let mlp = MLP::builder().input(2).hidden(3, NeuronType::Tanh).output(1).bias(false).build().unwrap();
+
+    let json = serde_json::to_string(&mlp).unwrap();
+    let restored: MLP = serde_json::from_str(json.as_str()).unwrap();
+
+    assert!(json.contains(r#""ntype":"Tanh""#));
+    assert_eq!(restored.to_string(), mlp.to_string());
+    assert_eq!(restored.state_dict(), mlp.state_dict());
+    assert!(restored.layers[1].neurons[0].b.is_none());
+
+    let custom = Layer::new(2, 1, NeuronType::custom(|x| x.silu()));
+    assert!(serde_json::to_string(&custom).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn can_save_and_load_mlp() {
+    let dir = std::env::temp_dir().join(format!("mikrograd_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mlp = MLP::builder().input(2).hidden(3, NeuronType::ReLU).output(1).build().unwrap();
+
+    for file in ["model.json", "model.bin"] {
+        let path = dir.join(file);
+        mlp.save(&path).unwrap();
+
+        let restored = MLP::load(&path).unwrap();
+        assert_eq!(restored.to_string(), mlp.to_string());
+        assert_eq!(restored.state_dict(), mlp.state_dict());
+
+        let mut target = MLP::builder().input(2).hidden(3, NeuronType::ReLU).output(1).build().unwrap();
+        target.load_weights(&path).unwrap();
+        assert_eq!(target.state_dict(), mlp.state_dict());
+
+        let mut other = MLP::builder().input(2).hidden(3, NeuronType::Tanh).output(1).build().unwrap();
+        assert!(other.load_weights(&path).unwrap_err().starts_with("architecture mismatch"));
+    }
+
+    let bytes = std::fs::read(dir.join("model.bin")).unwrap();
+    std::fs::write(dir.join("truncated.bin"), &bytes[..bytes.len() - 1]).unwrap();
+    assert_eq!(MLP::load(dir.join("truncated.bin")).unwrap_err(), "unexpected end of binary format");
+    let invalid = concat!(
+        r#"{"layers":[{"neurons":[{"ntype":"ReLU","w":[1.0],"b":null}]},"#,
+        r#"{"neurons":[{"ntype":"Linear","w":[1.0,2.0],"b":null}]}]}"#
+    );
+    std::fs::write(dir.join("invalid.json"), invalid).unwrap();
+    assert_eq!(MLP::load(dir.join("invalid.json")).unwrap_err(), "layer 1 has unexpected number of inputs");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn can_save_and_load_tied_mlp() {
+    let dir = std::env::temp_dir().join(format!("mikrograd_tied_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let encoder = Layer::new(3, 2, NeuronType::Tanh);
+    let mlp = MLP { layers: vec![encoder.tied_transposed(NeuronType::Linear), encoder] };
+
+    for file in ["model.json", "model.bin"] {
+        let path = dir.join(file);
+        mlp.save(&path).unwrap();
+
+        let restored = MLP::load(&path).unwrap();
+        assert_eq!(restored.parameters().count(), mlp.parameters().count());
+        assert_eq!(restored.state_dict(), mlp.state_dict());
+        assert_eq!(restored.layers[0].neurons[2].w[1].id(), restored.layers[1].neurons[1].w[2].id());
+    }
+
+    let json = serde_json::to_string(&mlp).unwrap();
+    assert_eq!(json.matches(r#"{"tied":"#).count(), 2 * 3);
+    let invalid = r#"{"layers":[{"neurons":[{"ntype":"Linear","w":[{"tied":0}],"b":null}]}]}"#;
+    assert_eq!(serde_json::from_str::<MLP>(invalid).unwrap_err().to_string(), "unknown tied parameter: 0");
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "onnx")]
+#[test]
+fn can_export_mlp_to_onnx() {
+    use prost::Message;
+
+    let mlp = MLP::builder().input(2).hidden(3, NeuronType::ReLU).output(1).bias(false).build().unwrap();
+
+    let model = onnx::ModelProto::decode(mlp.to_onnx().unwrap().as_slice()).unwrap();
+    let graph = model.graph.unwrap();
+
+    let nodes = graph.node.iter().map(|node| (node.op_type.as_str(), node.input.len())).collect::<Vec<_>>();
+    assert_eq!(nodes, [("Gemm", 2), ("Relu", 1), ("Gemm", 2)]);
+    assert_eq!(graph.node[0].input[0], "input");
+    assert_eq!(graph.node[1].input[0], graph.node[0].output[0]);
+    assert_eq!(graph.node[2].output[0], "output");
+    assert_eq!(graph.initializer[0].dims, [3, 2]);
+    assert_eq!(graph.initializer[0].float_data[1], mlp.layers[0].neurons[0].w[1].get_data() as f32);
+    assert_eq!(graph.initializer[1].name, "layers.1.weight");
+
+    let mlp = MLP::builder().input(2).output(2).build().unwrap();
+    let graph = onnx::ModelProto::decode(mlp.to_onnx().unwrap().as_slice()).unwrap().graph.unwrap();
+    assert_eq!(graph.node[0].input, ["input", "layers.0.weight", "layers.0.bias"]);
+    assert_eq!(graph.initializer[1].dims, [2]);
+    assert!(MLP::builder().input(2).hidden(1, NeuronType::custom(|x| x)).build().unwrap().to_onnx().is_err());
+    assert_eq!(new_mlp(2, &[0, 1]).to_onnx(), Err("layer 0 has no neurons".to_string()));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn can_import_weights_from_json_arrays() {
+    let path = std::env::temp_dir().join(format!("mikrograd_weights_{}.json", std::process::id()));
+    let mut mlp = MLP::builder().input(2).hidden(3, NeuronType::ReLU).output(1).build().unwrap();
+
+    std::fs::write(&path, "[[[1, 2], [3, 4], [5, 6]], [0.1, 0.2, 0.3], [[7, 8, 9]], [0.4]]").unwrap();
+    mlp.import_weights(&path).unwrap();
+    let data = mlp.parameters().map(|p| p.get_data()).collect::<Vec<_>>();
+    assert_eq!(data, [1., 2., 0.1, 3., 4., 0.2, 5., 6., 0.3, 7., 8., 9., 0.4]);
+
+    std::fs::write(&path, "[[[1, 2], [3, 4], [5, 6]], [0.1, 0.2, 0.3], [[7, 8]], [0.4]]").unwrap();
+    assert_eq!(mlp.import_weights(&path).unwrap_err(), "layer 1 expects weight matrix of [1, 3] shape");
+    assert_eq!(mlp.parameters().map(|p| p.get_data()).collect::<Vec<_>>(), data);
+
+    let mut mlp = MLP::builder().input(2).output(1).bias(false).build().unwrap();
+    std::fs::write(&path, "[[[1, 2]]]").unwrap();
+    mlp.import_weights(&path).unwrap();
+    assert_eq!(mlp.parameters().map(|p| p.get_data()).collect::<Vec<_>>(), [1., 2.]);
+
+    let layer = Layer::new(2, 2, NeuronType::Tanh);
+    let mut tied = MLP { layers: vec![layer.tied(), layer] };
+    std::fs::write(&path, "[[[1, 2], [3, 4]], [0.1, 0.2]]").unwrap();
+    tied.import_weights(&path).unwrap();
+    assert_eq!(tied.layers[1].weights(), [[1., 2.], [3., 4.]]);
+    assert_eq!(tied.layers[1].biases(), [0.1, 0.2]);
+
+    let layer = Layer::new(2, 2, NeuronType::Tanh);
+    let mut transposed = MLP { layers: vec![layer.tied_transposed(NeuronType::Tanh), layer] };
+    std::fs::write(&path, "[[[1, 2], [3, 4]], [0.1, 0.2], [[1, 3], [2, 4]], [0.3, 0.4]]").unwrap();
+    assert_eq!(transposed.import_weights(&path).unwrap_err(), "expected 8 values of unique parameters, got 12");
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn can_forward_any_module() {
+    fn output_size(module: &dyn Forward, x: &[Value]) -> usize {
+        module.forward(x).len()
+    }
+    let x = [Value::new(1.), Value::new(0.), Value::new(-1.), Value::new(2.)];
+
+    let neuron = Neuron::new(4, NeuronType::Tanh);
+    assert_eq!(neuron.forward(&x)[0].get_data(), neuron.call(&x).get_data());
+    assert_eq!(output_size(&Layer::new(4, 3, NeuronType::ReLU), &x), 3);
+
+    let cell = RNNCell::new(2, 3);
+    let expected = cell.unroll(&[x[..2].to_vec(), x[2..].to_vec()], None).pop().unwrap();
+    assert_eq!(
+        cell.forward(&x).iter().map(|h| h.get_data()).collect::<Vec<_>>(),
+        expected.iter().map(|h| h.get_data()).collect::<Vec<_>>()
+    );
+    assert_eq!(output_size(&GRUCell::new(2, 5), &x), 5);
+
+    let embedding = Embedding::new(3, 2);
+    let embedded = embedding.forward(&[Value::constant(2.), Value::constant(0.)]);
+    assert_eq!(embedded, [embedding.lookup(2), embedding.lookup(0)].concat());
+
+    let model = Sequential::new().with(embedding).with(RNNCell::new(2, 3)).with(Neuron::new(3, NeuronType::Linear));
+    assert_eq!(model.forward(&[Value::constant(1.), Value::constant(2.)]).len(), 1);
+}
+
+#[test]
+fn can_clone_modules_with_independent_parameters() {
+    let mlp = MLP::builder().input(2).hidden(3, NeuronType::ReLU).output(1).build().unwrap();
+    let mut copy = mlp.clone();
+    assert_eq!(copy.state_dict(), mlp.state_dict());
+    assert!(copy.parameters().zip(mlp.parameters()).all(|(lhs, rhs)| lhs.id() != rhs.id()));
+
+    let x = [Value::new(1.), Value::new(-1.)];
+    copy.call(&x)[0].backward();
+    copy.parameters_mut().for_each(|p| p.set_data(p.get_data() + 1.));
+    assert!(mlp.parameters().all(|p| p.get_grad() == 0.));
+    assert!(copy.parameters().zip(mlp.parameters()).all(|(lhs, rhs)| lhs.get_data() == rhs.get_data() + 1.));
+
+    let embedding = Embedding::new(2, 3);
+    let mut copy = embedding.clone();
+    copy.parameters_mut().for_each(|p| p.set_data(42.));
+    assert!(embedding.parameters().all(|p| p.get_data() != 42.));
+}
+
+#[test]
+fn can_clone_tied_mlp_keeping_parameters_shared() {
+    let encoder = Layer::new(3, 2, NeuronType::Tanh);
+    let tied = MLP { layers: vec![encoder.tied_transposed(NeuronType::Linear), encoder] };
+    assert_eq!(tied.parameters().count(), 2 * 3 + 3 + 2);
+
+    let copy = tied.clone();
+    assert_eq!(copy.parameters().count(), tied.parameters().count());
+    assert_eq!(copy.state_dict(), tied.state_dict());
+    assert!(copy.parameters().all(|lhs| tied.parameters().all(|rhs| lhs.id() != rhs.id())));
+
+    copy.layers[1].neurons[0].w[2].clone().set_data(42.);
+    assert_eq!(copy.layers[0].neurons[2].w[0].get_data(), 42.);
+    assert_ne!(tied.layers[0].neurons[2].w[0].get_data(), 42.);
+}
+
+#[test]
+fn can_get_and_set_layer_weights() {
+    let mut layer = Layer::new(2, 3, NeuronType::ReLU);
+
+    layer.set_weights(&[vec![1., 2.], vec![3., 4.], vec![5., 6.]]).unwrap();
+    layer.set_biases(&[0.1, 0.2, 0.3]).unwrap();
+    assert_eq!(layer.weights(), [[1., 2.], [3., 4.], [5., 6.]]);
+    assert_eq!(layer.biases(), [0.1, 0.2, 0.3]);
+    assert_eq!(layer.neurons[1].parameters().map(|p| p.get_data()).collect::<Vec<_>>(), [3., 4., 0.2]);
+
+    assert_eq!(
+        layer.set_weights(&[vec![1., 2.], vec![3.], vec![5., 6.]]),
+        Err("expected weight matrix of [3, 2] shape".to_string())
+    );
+    assert_eq!(layer.set_biases(&[1.]), Err("expected 3 biases, got 1".to_string()));
+    assert_eq!(layer.weights(), [[1., 2.], [3., 4.], [5., 6.]]);
+
+    let mut layer = Layer::new(2, 1, NeuronType::Linear).with_bias(false);
+    assert_eq!(layer.biases(), [0.]);
+    assert!(layer.set_biases(&[1.]).is_err());
+}
+
+#[test]
+fn can_tie_weights_between_layers() {
+    let encoder = Layer::new(3, 2, NeuronType::Tanh);
+    let decoder = encoder.tied_transposed(NeuronType::Linear);
+    assert_eq!(
+        decoder.weights(),
+        (0..3).map(|col| encoder.weights().iter().map(|row| row[col]).collect::<Vec<_>>()).collect::<Vec<_>>()
+    );
+
+    let mut model = Sequential::new().with(encoder).with(decoder);
+    assert_eq!(model.parameters().count(), 2 * 3 + 2 + 3);
+    assert_eq!(model.named_parameters().count(), model.parameters().count());
+    assert_eq!(model.state_dict().last().unwrap().0, "modules.1.neurons.2.b");
+
+    let x = [Value::new(1.), Value::new(2.), Value::new(-1.)];
+    let y = model.forward(&x);
+    y.into_iter().sum::<Value>().backward();
+    let shared = model.parameters().next().unwrap().clone();
+    let grad = shared.get_grad();
+    model.zero_grad();
+    assert_ne!(grad, 0.);
+    assert_eq!(shared.get_grad(), 0.);
+
+    let layer = Layer::new(2, 2, NeuronType::ReLU);
+    let model = Sequential::new().with(layer.tied()).with(layer);
+    assert_eq!(model.parameters().count(), 6);
+}
+
+#[test]
+fn can_create_mlp_with_activation_per_layer() {
+    let mlp = MLP::builder()
+        .input(2)
+        .hidden(4, NeuronType::Tanh)
+        .hidden(3, NeuronType::ReLU)
+        .hidden(1, NeuronType::Sigmoid)
+        .build()
+        .unwrap();
+
+    let activations = mlp.layers.iter().map(|layer| format!("{:?}", layer.neurons[0].ntype)).collect::<Vec<_>>();
+    assert_eq!(activations, ["Tanh", "ReLU", "Sigmoid"]);
+    assert_eq!(mlp.parameters().count(), 3 * 4 + 5 * 3 + 4);
+
+    let output = mlp.call(&[Value::new(1.), Value::new(-1.)])[0].get_data();
+    assert!(output > 0. && output < 1.);
+
+    let activations = [NeuronType::Tanh, NeuronType::Sigmoid];
+    let mlp = super::super::new_mlp_with_activations(2, &[3, 1], &activations).unwrap();
+    assert_eq!(mlp.parameters().count(), 3 * 3 + 4);
+    assert!(matches!(mlp.layers[1].neurons[0].ntype, NeuronType::Sigmoid));
+    assert_eq!(
+        super::super::new_mlp_with_activations(2, &[4, 3, 1], &activations).err(),
+        Some("expected 3 activations, got 2".to_string())
+    );
+}
+
+#[test]
+fn can_create_mlp_with_hidden_activation() {
+    let mlp = MLP::new(2, &[3, 3, 1], NeuronType::Tanh);
+
+    assert!(mlp.layers[..2].iter().flat_map(|layer| layer.neurons.iter()).all(|n| matches!(n.ntype, NeuronType::Tanh)));
+    assert!(matches!(mlp.layers[2].neurons[0].ntype, NeuronType::Linear));
+    assert!(mlp.to_string().starts_with("MLP of [Layer of [TanhNeuron(2),"));
+}
+
+#[test]
+fn can_apply_neuron_activations() {
+    let create = |ntype| Neuron { w: vec![Value::new(1.)], b: Some(Value::new(0.)), ntype };
+
+    assert_eq!(create(NeuronType::Tanh).call(&[Value::new(0.5)]).get_data(), 0.5_f64.tanh());
+    assert_eq!(create(NeuronType::Sigmoid).call(&[Value::new(0.)]).get_data(), 0.5);
+    assert_eq!(create(NeuronType::ReLU).call(&[Value::new(-1.)]).get_data(), 0.);
+}
+
+#[test]
+fn can_use_custom_activation() {
+    let layer = Layer::new(1, 2, NeuronType::custom(|x| x.powi(2)));
+    layer.neurons.iter().for_each(|neuron| neuron.w[0].clone().set_data(3.));
+
+    let result = layer.call(&[Value::new(2.)]);
+
+    assert_eq!(result.iter().map(|v| v.get_data()).collect::<Vec<_>>(), vec![36., 36.]);
+    assert_eq!(layer.to_string(), "Layer of [CustomNeuron(1),CustomNeuron(1)]");
+}
+
 #[test]
 fn can_process_data_in_neuron() {
-    let neuron = Neuron { w: vec![Value::new(10.), Value::new(100.)], b: Value::new(3.), ntype: NeuronType::Linear };
+    let neuron =
+        Neuron { w: vec![Value::new(10.), Value::new(100.)], b: Some(Value::new(3.)), ntype: NeuronType::Linear };
 
     let result = neuron.call(&[Value::new(1.2), Value::new(1.3)]);
 
     assert_eq!(result.get_data(), 145.);
     assert_eq!(result.get_grad(), 0.);
+    assert_eq!(result.graph_stats().ops.get("fma"), Some(&2));
+}
+
+#[test]
+fn can_report_gradients_per_layer() {
+    let mlp = MLP::new(2, &[3, 1], NeuronType::ReLU);
+    assert!(mlp.grad_report().iter().all(|stats| stats.max == 0.));
+
+    let output = mlp.call(&[Value::new(1.), Value::new(-2.)]);
+    output[0].backward();
+
+    let report = mlp.grad_report();
+    assert_eq!(report.iter().map(|stats| stats.count).collect::<Vec<_>>(), vec![9, 4]);
+    let grads = mlp.layers[1].parameters().map(|p| p.get_grad().abs()).collect::<Vec<_>>();
+    assert_eq!(report[1].max, grads.iter().cloned().fold(f64::NEG_INFINITY, f64::max));
+    assert_eq!(report[1].min, grads.iter().cloned().fold(f64::INFINITY, f64::min));
+    assert!(report.iter().all(|stats| stats.min <= stats.mean && stats.mean <= stats.max));
+
+    let neuron_report = mlp.layers[1].neurons[0].grad_report();
+    assert_eq!(neuron_report, vec![report[1].clone()]);
+    assert_eq!(GradStats::new(std::iter::empty()).to_string(), "GradStats[count=0, min=0, max=0, mean=0]");
+}
+
+#[test]
+fn can_apply_dropout() {
+    use rand::SeedableRng;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+    let x = (0..1000).map(|_| Value::new(1.)).collect::<Vec<_>>();
+    let mut dropout = Dropout::new(0.25);
+
+    let result = dropout.call_with_rng(x.as_slice(), &mut rng);
+    let zeros = result.iter().filter(|v| v.get_data() == 0.).count();
+    assert!(result.iter().all(|v| v.get_data() == 0. || (v.get_data() - 4. / 3.).abs() < 1E-12));
+    assert!((200..300).contains(&zeros));
+
+    result[0].backward();
+    assert_eq!(x[0].get_grad(), result[0].get_data());
+
+    dropout.eval();
+    let result = dropout.call(x.as_slice());
+    assert!(result.iter().zip(x.iter()).all(|(r, x)| r == x));
+    assert_eq!((dropout.to_string(), dropout.parameters().count()), ("Dropout(0.25)".to_string(), 0));
+}
+
+#[test]
+fn can_apply_conv2d() {
+    let x = (1..=18).map(|v| Value::new(v as f64)).collect::<Vec<_>>();
+    let conv = Conv2d::new(2, 1, (3, 3), 2).with_init(Init::Zeros);
+    // sums all channels within a kernel window
+    conv.filters[0].w.iter().for_each(|w| w.clone().set_data(1.));
+
+    let result = conv.call(x.as_slice());
+
+    assert_eq!(conv.output_size(), (2, 2));
+    assert_eq!(result.iter().map(|v| v.get_data()).collect::<Vec<_>>(), vec![60., 68., 84., 92.]);
+    assert_eq!(conv.parameters().count(), 9);
+
+    result[0].backward();
+    assert_eq!(x.iter().map(|v| v.get_grad()).filter(|&grad| grad == 1.).count(), 8);
+}
+
+#[test]
+fn can_apply_conv2d_with_stride_and_padding() {
+    let x = (1..=4).map(|v| Value::new(v as f64)).collect::<Vec<_>>();
+    let conv = Conv2d::new(1, 2, (2, 2), 3).with_padding(1).with_stride(2).with_init(Init::Zeros);
+    conv.filters.iter().flat_map(|filter| filter.w.iter()).for_each(|w| w.clone().set_data(1.));
+
+    let result = conv.call(x.as_slice());
+
+    assert_eq!(conv.output_size(), (1, 1));
+    assert_eq!(result.iter().map(|v| v.get_data()).collect::<Vec<_>>(), vec![10., 10.]);
+    assert_eq!(conv.to_string(), "Conv2d(1, 2, kernel=3, stride=2, padding=1)");
+}
+
+#[test]
+fn can_apply_conv2d_with_kernel_larger_than_input() {
+    let x = (1..=4).map(|v| Value::new(v as f64)).collect::<Vec<_>>();
+    let conv = Conv2d::new(1, 1, (2, 2), 5);
+
+    assert_eq!(conv.output_size(), (0, 0));
+    assert!(conv.call(x.as_slice()).is_empty());
+    assert_eq!(conv.clone().with_padding(1).output_size(), (0, 0));
+    assert_eq!(conv.with_padding(2).output_size(), (2, 2));
+}
+
+#[test]
+fn can_apply_pooling_1d() {
+    let x = [1., 5., 3., 2., 4., 0., 7.].into_iter().map(Value::new).collect::<Vec<_>>();
+
+    let result = MaxPool1d::new(2).call(x.as_slice());
+    assert_eq!(result.iter().map(|v| v.get_data()).collect::<Vec<_>>(), vec![5., 3., 4.]);
+    super::super::functions::sum(result.as_slice()).backward();
+    assert_eq!(x.iter().map(|v| v.get_grad()).collect::<Vec<_>>(), vec![0., 1., 1., 0., 1., 0., 0.]);
+
+    let pool = AvgPool1d::new(3).with_stride(2);
+    let result = pool.call(x.as_slice());
+    assert_eq!(result.iter().map(|v| v.get_data()).collect::<Vec<_>>(), vec![3., 3., 11. / 3.]);
+    assert_eq!(pool.to_string(), "AvgPool1d(kernel=3, stride=2)");
+}
+
+#[test]
+fn can_apply_pooling_2d() {
+    let x = (0..32).map(|v| Value::new(v as f64)).collect::<Vec<_>>();
+
+    let pool = MaxPool2d::new(2, (4, 4), 2);
+    let result = pool.call(x.as_slice());
+    assert_eq!(pool.output_size(), (2, 2));
+    assert_eq!(result.iter().map(|v| v.get_data()).collect::<Vec<_>>(), vec![5., 7., 13., 15., 21., 23., 29., 31.]);
+    result[0].backward();
+    assert_eq!((x[5].get_grad(), x[4].get_grad()), (1., 0.));
+
+    let result = AvgPool2d::new(2, (4, 4), 3).with_stride(1).call(x.as_slice());
+    assert_eq!(result.iter().map(|v| v.get_data()).collect::<Vec<_>>(), vec![5., 6., 9., 10., 21., 22., 25., 26.]);
+}
+
+#[test]
+fn can_unroll_rnn_cell() {
+    let cell = RNNCell::new(1, 2).with_init(Init::Zeros);
+    cell.layer.neurons[0].w.iter().for_each(|w| w.clone().set_data(0.5));
+    let xs = [1., 2., 3.].into_iter().map(|x| vec![Value::new(x)]).collect::<Vec<_>>();
+
+    let hs = cell.unroll(xs.as_slice(), None);
+
+    assert_eq!((hs.len(), cell.parameters().count(), cell.to_string()), (3, 8, "RNNCell(1, 2)".to_string()));
+    let h1 = 0.5_f64.tanh();
+    let h2 = (1. + 0.5 * h1).tanh();
+    assert!((hs[1][0].get_data() - h2).abs() < 1E-12);
+    assert!(hs.iter().all(|h| h[1].get_data() == 0.));
+
+    hs[2][0].backward();
+    assert!(xs.iter().all(|x| x[0].get_grad() > 0.));
+    assert!(cell.layer.neurons[0].w[1].get_grad() > 0.);
+}
+
+#[test]
+fn can_unroll_gru_cell() {
+    let cell = GRUCell::new(2, 3);
+    assert!(cell.parameters().all(|p| p.get_data().abs() <= 1. / 3_f64.sqrt()));
+    assert_eq!((cell.parameters().count(), cell.to_string()), (57, "GRUCell(2, 3)".to_string()));
+
+    let xs = (0..4).map(|idx| vec![Value::new(idx as f64), Value::new(-1.)]).collect::<Vec<_>>();
+    let hs = cell.unroll(xs.as_slice(), None);
+    assert_eq!(hs.len(), 4);
+    assert!(hs.iter().flatten().all(|h| h.get_data().abs() < 1.));
+
+    super::super::functions::sum(hs[3].as_slice()).backward();
+    assert!(cell.parameters().any(|p| p.get_grad() != 0.));
+
+    // weights are sampled once
+    let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(42);
+    let init = Init::Uniform { low: -1. / 3_f64.sqrt(), high: 1. / 3_f64.sqrt() };
+    let expected = (0..5).map(|_| init.sample(&mut rng, 5, 3)).collect::<Vec<_>>();
+    let cell = GRUCell::new_with_rng(2, 3, &mut <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(42));
+    assert_eq!(cell.update.weights()[0], expected);
+}
+
+#[test]
+fn can_keep_hidden_state_when_gru_update_gate_is_saturated() {
+    let cell = GRUCell::new(1, 1);
+    cell.update.neurons[0].b.clone().unwrap().set_data(100.);
+    let h = cell.call(&[Value::new(1.)], &[Value::new(0.7)]);
+
+    assert!((h[0].get_data() - 0.7).abs() < 1E-9);
+}
+
+#[test]
+fn can_look_up_embeddings() {
+    let embedding = Embedding::new(5, 3);
+    assert_eq!((embedding.parameters().count(), embedding.to_string()), (15, "Embedding(5, 3)".to_string()));
+
+    let rows = embedding.call(&[1, 3, 1]);
+    assert_eq!(rows[0], embedding.lookup(1).to_vec());
+
+    super::super::functions::sum(rows.concat().as_slice()).backward();
+    let grads = embedding.weights.iter().map(|row| row[0].get_grad()).collect::<Vec<_>>();
+    assert_eq!(grads, vec![0., 2., 0., 1., 0.]);
+}
+
+#[test]
+fn can_stack_modules_in_sequential() {
+    let mut model = Sequential::new()
+        .with(Layer::new(2, 4, NeuronType::Tanh))
+        .with(Dropout::new(0.5))
+        .with(MaxPool1d::new(2))
+        .with(MLP::new(2, &[3, 1], NeuronType::ReLU));
+
+    assert_eq!((model.len(), model.parameters().count()), (4, 12 + 9 + 4));
+    assert!(model.to_string().starts_with("Sequential of [Layer of [TanhNeuron(2),"));
+
+    model.eval();
+    let x = [Value::new(0.5), Value::new(-1.)];
+    let result = model.call(&x);
+    assert_eq!(result.len(), 1);
+    assert_eq!(model.forward(&x)[0].get_data(), result[0].get_data());
+
+    result[0].backward();
+    assert_eq!(model.grad_report().len(), 4);
+    model.zero_grad();
+    assert!(model.parameters().all(|p| p.get_grad() == 0.));
+}
+
+#[test]
+fn can_apply_residual_block() {
+    let x = [Value::new(1.), Value::new(-2.)];
+
+    let block = Residual::new(Layer::new(2, 2, NeuronType::ReLU).with_init(Init::Zeros), 2, 2);
+    let result = block.call(&x);
+    assert_eq!(result.iter().map(|v| v.get_data()).collect::<Vec<_>>(), vec![1., -2.]);
+    assert_eq!((block.parameters().count(), block.to_string().starts_with("Residual of [Layer")), (6, true));
+
+    let block = Residual::new(Layer::new(2, 3, NeuronType::Tanh), 2, 3);
+    let result = block.forward(&x);
+    assert_eq!((result.len(), block.parameters().count()), (3, 9 + 6));
+
+    result[0].backward();
+    assert!(block.projection.as_ref().unwrap().parameters().any(|p| p.get_grad() != 0.));
+}
+
+#[test]
+fn can_initialize_residual_rnn_and_embedding_with_seeded_rng() {
+    use rand::{rngs::StdRng, SeedableRng};
+    fn data(module: &dyn Module) -> Vec<f64> {
+        module.parameters().map(|p| p.get_data()).collect()
+    }
+
+    let residual =
+        |seed| Residual::new_with_rng(Layer::new(2, 3, NeuronType::ReLU), 2, 3, &mut StdRng::seed_from_u64(seed));
+    let rnn = |seed| RNNCell::new(2, 3).with_init_rng(Init::Xavier, &mut StdRng::seed_from_u64(seed));
+    let embedding = |seed| Embedding::new(4, 2).with_init_rng(Init::He, &mut StdRng::seed_from_u64(seed));
+
+    let projection = |block: &Residual| data(block.projection.as_ref().unwrap());
+    assert_eq!(projection(&residual(1)), projection(&residual(1)));
+    assert_ne!(projection(&residual(1)), projection(&residual(2)));
+    assert_eq!(data(&rnn(1)), data(&rnn(1)));
+    assert_ne!(data(&rnn(1)), data(&rnn(2)));
+    assert_eq!(data(&embedding(1)), data(&embedding(1)));
+    assert_ne!(data(&embedding(1)), data(&embedding(2)));
+}
+
+#[test]
+fn can_apply_attention() {
+    let tokens = [[1., 0.], [0., 1.], [1., 1.]]
+        .iter()
+        .map(|token| token.iter().cloned().map(Value::new).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let attention = Attention::new(2, 3);
+    assert_eq!((attention.parameters().count(), attention.to_string()), (27, "Attention(2, 3)".to_string()));
+
+    let result = attention.call(tokens.as_slice());
+    assert_eq!((result.len(), result[0].len()), (3, 3));
+    assert_eq!(attention.forward(tokens.concat().as_slice()).len(), 9);
+
+    // the first token attends only to itself, so its output is its value projection
+    let causal = attention.with_causal_mask(true);
+    let first = causal.call(tokens.as_slice()).remove(0);
+    let value = causal.value.call(tokens[0].as_slice());
+    assert!(first.iter().zip(value.iter()).all(|(f, v)| (f.get_data() - v.get_data()).abs() < 1E-12));
+
+    super::super::functions::sum(result[2].as_slice()).backward();
+    assert!(causal.query.parameters().any(|p| p.get_grad() != 0.));
+
+    // weights are sampled once
+    let mut rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(42);
+    let expected = (0..2).map(|_| Init::Xavier.sample(&mut rng, 2, 3)).collect::<Vec<_>>();
+    let attention = Attention::new_with_rng(2, 3, &mut <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(42));
+    assert_eq!(attention.query.weights()[0], expected);
 }
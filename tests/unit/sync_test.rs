@@ -0,0 +1,40 @@
+use super::*;
+
+#[test]
+fn can_share_graph_between_threads() {
+    let x = new_value(2.);
+
+    let handles = (0..4)
+        .map(|idx| {
+            let x = x.clone();
+            std::thread::spawn(move || {
+                let result = (&x * 3.).tanh() * idx as f64;
+                result.backward();
+                result.get_data()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let results = handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>();
+
+    assert_eq!(results, (0..4).map(|idx| 6_f64.tanh() * idx as f64).collect::<Vec<_>>());
+    assert!((x.get_grad() - 6. * 3. * (1. - 6_f64.tanh().powi(2))).abs() < 1E-9);
+}
+
+#[test]
+fn can_run_backward_in_parallel_on_wide_graph() {
+    let (w, b) = (new_value(0.5), new_value(-1.));
+    let xs = (0..256).map(|idx| new_value(idx as f64 / 256.)).collect::<Vec<_>>();
+
+    let losses = xs.iter().map(|x| (x * &w + &b).powi(2)).collect::<Vec<_>>();
+    let loss = sum(losses.as_slice());
+    loss.backward();
+
+    let expected = |x: f64| 2. * (x * 0.5 - 1.);
+    let (w_grad, b_grad) = (0..256)
+        .map(|idx| idx as f64 / 256.)
+        .fold((0., 0.), |(w_grad, b_grad), x| (w_grad + expected(x) * x, b_grad + expected(x)));
+    assert!((w.get_grad() - w_grad).abs() < 1E-9);
+    assert!((b.get_grad() - b_grad).abs() < 1E-9);
+    assert!(xs.iter().all(|x| (x.get_grad() - expected(x.get_data()) * 0.5).abs() < 1E-12));
+}
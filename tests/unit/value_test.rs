@@ -4,6 +4,16 @@ fn create_value(data: f64) -> Value {
     Value::new(data)
 }
 
+fn assert_numerical_grad(x: f64, op: impl Fn(&Value) -> Value) {
+    let eps = 1E-6;
+    let expected = (op(&create_value(x + eps)).get_data() - op(&create_value(x - eps)).get_data()) / (2. * eps);
+
+    let x = create_value(x);
+    op(&x).backward();
+
+    assert!((x.get_grad() - expected).abs() < 1E-6, "analytical: {}, numerical: {}", x.get_grad(), expected);
+}
+
 #[test]
 fn can_sum_values() {
     let lhs = create_value(3.);
@@ -13,17 +23,17 @@ fn can_sum_values() {
     let result2 = lhs + rhs;
 
     assert_eq!(result1.get_data(), 5.);
-    assert_eq!(result1.op, "add");
+    assert_eq!(result1.op().to_string(), "add");
     assert_eq!(result1.children.len(), 2);
     assert_eq!(result2.get_data(), 5.);
 
     let result = create_value(3.) + 2.;
     assert_eq!(result.get_data(), 5.);
-    assert_eq!(result.op, "add");
+    assert_eq!(result.op().to_string(), "add");
 
     let result = 3. + create_value(2.) + 2.;
     assert_eq!(result.get_data(), 7.);
-    assert_eq!(result.op, "add");
+    assert_eq!(result.op().to_string(), "add");
 }
 
 #[test]
@@ -34,16 +44,16 @@ fn can_multiply_values() {
     let result = lhs * rhs;
 
     assert_eq!(result.get_data(), 6.);
-    assert_eq!(result.op, "mul");
+    assert_eq!(result.op().to_string(), "mul");
     assert_eq!(result.children.len(), 2);
 
     let result = create_value(3.4) * 2.;
     assert_eq!(result.get_data(), 6.8);
-    assert_eq!(result.op, "mul");
+    assert_eq!(result.op().to_string(), "mul");
 
     let result = 2. * create_value(3.4);
     assert_eq!(result.get_data(), 6.8);
-    assert_eq!(result.op, "mul");
+    assert_eq!(result.op().to_string(), "mul");
 }
 
 #[test]
@@ -53,16 +63,16 @@ fn can_subtract_values() {
 
     let result = lhs - rhs;
     assert_eq!(result.get_data(), 1.);
-    assert_eq!(result.op, "sub");
+    assert_eq!(result.op().to_string(), "sub");
     assert_eq!(result.children.len(), 2);
 
     let result = create_value(3.) - 2.;
     assert_eq!(result.get_data(), 1.);
-    assert_eq!(result.op, "sub");
+    assert_eq!(result.op().to_string(), "sub");
 
     let result = 3. - create_value(2.);
     assert_eq!(result.get_data(), 1.);
-    assert_eq!(result.op, "sub");
+    assert_eq!(result.op().to_string(), "sub");
 }
 
 #[test]
@@ -73,23 +83,25 @@ fn can_divide_values() {
     let result = lhs / rhs;
 
     assert_eq!(result.get_data(), 1.5);
-    assert_eq!(result.op, "div");
+    assert_eq!(result.op().to_string(), "div");
     assert_eq!(result.children.len(), 2);
 
     let result = create_value(5.) / 2.;
     assert_eq!(result.get_data(), 2.5);
-    assert_eq!(result.op, "div");
+    assert_eq!(result.op().to_string(), "div");
 
     let result = 5. / create_value(2.);
     assert_eq!(result.get_data(), 2.5);
-    assert_eq!(result.op, "div");
+    assert_eq!(result.op().to_string(), "div");
 }
 
 #[test]
 fn can_pow_value() {
     let result = create_value(5.).pow(2.);
     assert_eq!(result.get_data(), 25.);
-    assert_eq!(result.op, "pow");
+    assert_eq!(result.op().to_string(), "pow");
+    assert_eq!(*result.op(), Op::Pow { exp: 2. });
+    assert_eq!(*create_value(1.).op(), Op::Leaf);
     assert_eq!(result.children.len(), 1);
 }
 
@@ -97,12 +109,12 @@ fn can_pow_value() {
 fn can_relu_value() {
     let result = create_value(5.).relu();
     assert_eq!(result.get_data(), 5.);
-    assert_eq!(result.op, "relu");
+    assert_eq!(result.op().to_string(), "relu");
     assert_eq!(result.children.len(), 1);
 
     let result = create_value(-1.).relu();
     assert_eq!(result.get_data(), 0.);
-    assert_eq!(result.op, "relu");
+    assert_eq!(result.op().to_string(), "relu");
     assert_eq!(result.children.len(), 1);
 }
 
@@ -143,3 +155,1125 @@ fn can_calculate_reference_gradients() {
     assert_eq!(z.get_data(), -10.);
     assert_eq!(h.get_data(), 100.);
 }
+
+#[test]
+fn can_tanh_value() {
+    let result = create_value(0.5).tanh();
+    assert_eq!(result.get_data(), 0.5_f64.tanh());
+    assert_eq!(result.op().to_string(), "tanh");
+    assert_eq!(result.children.len(), 1);
+
+    let x = create_value(0.5);
+    let y = x.tanh();
+    y.backward();
+    assert!((x.get_grad() - (1. - 0.5_f64.tanh().powi(2))).abs() < 1E-12);
+}
+
+#[test]
+fn can_sigmoid_value() {
+    let result = create_value(0.).sigmoid();
+    assert_eq!(result.get_data(), 0.5);
+    assert_eq!(result.op().to_string(), "sigmoid");
+    assert_eq!(result.children.len(), 1);
+
+    assert_eq!(create_value(-1000.).sigmoid().get_data(), 0.);
+    assert_eq!(create_value(1000.).sigmoid().get_data(), 1.);
+
+    let x = create_value(0.);
+    let y = x.sigmoid();
+    y.backward();
+    assert_eq!(x.get_grad(), 0.25);
+}
+
+#[test]
+fn can_exp_value() {
+    let result = create_value(2.).exp();
+    assert_eq!(result.get_data(), 2_f64.exp());
+    assert_eq!(result.op().to_string(), "exp");
+    assert_eq!(result.children.len(), 1);
+
+    let x = create_value(2.);
+    let y = x.exp() * 3.;
+    y.backward();
+    assert_eq!(x.get_grad(), 3. * 2_f64.exp());
+}
+
+#[test]
+fn can_ln_value() {
+    let result = create_value(std::f64::consts::E).ln();
+    assert_eq!(result.get_data(), 1.);
+    assert_eq!(result.op().to_string(), "ln");
+    assert_eq!(result.children.len(), 1);
+
+    assert!(create_value(0.).ln().get_data().is_nan());
+    assert!(create_value(-1.).ln().get_data().is_nan());
+
+    assert_numerical_grad(0.3, |x| x.ln());
+    assert_numerical_grad(5., |x| x.ln() * x);
+}
+
+#[test]
+fn can_log_value_with_base() {
+    let result = create_value(8.).log2();
+    assert!((result.get_data() - 3.).abs() < 1E-12);
+    assert_eq!(result.op().to_string(), "log2");
+
+    let result = create_value(1000.).log10();
+    assert!((result.get_data() - 3.).abs() < 1E-12);
+    assert_eq!(result.op().to_string(), "log10");
+
+    let result = create_value(81.).log(3.);
+    assert!((result.get_data() - 4.).abs() < 1E-12);
+    assert_eq!(result.op().to_string(), "log");
+    assert!(create_value(-1.).log(3.).get_data().is_nan());
+
+    assert_numerical_grad(0.7, |x| x.log2());
+    assert_numerical_grad(3., |x| x.log10());
+    assert_numerical_grad(2.5, |x| x.log(5.));
+}
+
+#[test]
+fn can_sqrt_value() {
+    let result = create_value(9.).sqrt();
+    assert_eq!(result.get_data(), 3.);
+    assert_eq!(result.op().to_string(), "sqrt");
+    assert_eq!(result.children.len(), 1);
+
+    assert_numerical_grad(2., |x| x.sqrt());
+
+    let x = create_value(0.);
+    x.sqrt().backward();
+    assert!(x.get_grad().is_finite());
+
+    let x = create_value(0.);
+    x.sqrt_eps(0.5).backward();
+    assert_eq!(x.get_grad(), 1.);
+}
+
+#[test]
+fn can_abs_value() {
+    let result = create_value(-3.).abs();
+    assert_eq!(result.get_data(), 3.);
+    assert_eq!(result.op().to_string(), "abs");
+    assert_eq!(result.children.len(), 1);
+
+    let parameters = [-2., 0., 2.].map(create_value);
+    parameters.iter().map(|x| x.abs()).sum::<Value>().backward();
+    assert_eq!(parameters.map(|p| p.get_grad()), [-1., 0., 1.]);
+}
+
+#[test]
+fn can_negate_value() {
+    let x = create_value(3.);
+
+    let result = -&x;
+    assert_eq!(result.get_data(), -3.);
+    assert_eq!(result.op().to_string(), "neg");
+
+    let result = -x.clone() + 1.;
+    assert_eq!(result.get_data(), -2.);
+
+    result.backward();
+    assert_eq!(x.get_grad(), -1.);
+}
+
+#[test]
+fn can_apply_trigonometric_functions() {
+    let result = create_value(std::f64::consts::FRAC_PI_2).sin();
+    assert_eq!(result.get_data(), 1.);
+    assert_eq!(result.op().to_string(), "sin");
+    assert_eq!(result.children.len(), 1);
+
+    let result = create_value(0.).cos();
+    assert_eq!(result.get_data(), 1.);
+    assert_eq!(result.op().to_string(), "cos");
+    assert_eq!(result.children.len(), 1);
+
+    assert_numerical_grad(0.3, |x| x.sin());
+    assert_numerical_grad(0.3, |x| x.cos());
+    assert_numerical_grad(1.2, |x| x.sin() * x.cos());
+}
+
+#[test]
+fn can_apply_tan_and_atan() {
+    let result = create_value(0.).tan();
+    assert_eq!(result.get_data(), 0.);
+    assert_eq!(result.op().to_string(), "tan");
+    assert_eq!(result.children.len(), 1);
+
+    let result = create_value(1.).atan();
+    assert_eq!(result.get_data(), std::f64::consts::FRAC_PI_4);
+    assert_eq!(result.op().to_string(), "atan");
+    assert_eq!(result.children.len(), 1);
+
+    assert_numerical_grad(0.7, |x| x.tan());
+    assert_numerical_grad(-2., |x| x.atan());
+}
+
+#[test]
+fn can_apply_hyperbolic_functions() {
+    let result = create_value(0.).sinh();
+    assert_eq!(result.get_data(), 0.);
+    assert_eq!(result.op().to_string(), "sinh");
+    assert_eq!(result.children.len(), 1);
+
+    let result = create_value(0.).cosh();
+    assert_eq!(result.get_data(), 1.);
+    assert_eq!(result.op().to_string(), "cosh");
+    assert_eq!(result.children.len(), 1);
+
+    assert_numerical_grad(0.8, |x| x.sinh());
+    assert_numerical_grad(-1.5, |x| x.cosh());
+}
+
+#[test]
+fn can_elu_value() {
+    let result = create_value(2.).elu(1.);
+    assert_eq!(result.get_data(), 2.);
+    assert_eq!(result.op().to_string(), "elu");
+    assert_eq!(result.children.len(), 1);
+
+    let result = create_value(-1.).elu(0.5);
+    assert_eq!(result.get_data(), 0.5 * (-1_f64).exp_m1());
+
+    assert_numerical_grad(1.5, |x| x.elu(0.5));
+    assert_numerical_grad(-1.5, |x| x.elu(0.5));
+}
+
+#[test]
+fn can_silu_value() {
+    let result = create_value(2.).silu();
+    assert_eq!(result.get_data(), 2. * create_value(2.).sigmoid().get_data());
+    assert_eq!(result.op().to_string(), "silu");
+    assert_eq!(result.children.len(), 1);
+
+    assert_numerical_grad(0.7, |x| x.silu());
+    assert_numerical_grad(-3., |x| x.silu());
+}
+
+#[test]
+fn can_softplus_value() {
+    let result = create_value(0.).softplus();
+    assert_eq!(result.get_data(), 2_f64.ln());
+    assert_eq!(result.op().to_string(), "softplus");
+    assert_eq!(result.children.len(), 1);
+
+    assert_eq!(create_value(1000.).softplus().get_data(), 1000.);
+    assert_eq!(create_value(-1000.).softplus().get_data(), 0.);
+
+    assert_numerical_grad(1.3, |x| x.softplus());
+    assert_numerical_grad(-2., |x| x.softplus());
+}
+
+#[test]
+fn can_clamp_value() {
+    let result = create_value(2.).clamp(0., 1.);
+    assert_eq!(result.get_data(), 1.);
+    assert_eq!(result.op().to_string(), "clamp");
+    assert_eq!(result.children.len(), 1);
+
+    let parameters = [-1., 0.5, 2.].map(create_value);
+    parameters.iter().map(|x| x.clamp(0., 1.) * 2.).sum::<Value>().backward();
+    assert_eq!(parameters.map(|p| p.get_grad()), [0., 2., 0.]);
+}
+
+#[test]
+fn can_get_max_and_min_of_values() {
+    let (a, b) = (create_value(2.), create_value(3.));
+
+    let result = a.max(&b);
+    assert_eq!(result.get_data(), 3.);
+    assert_eq!(result.op().to_string(), "max");
+    assert_eq!(result.children.len(), 2);
+    result.backward();
+    assert_eq!((a.get_grad(), b.get_grad()), (0., 1.));
+
+    let (a, b) = (create_value(2.), create_value(3.));
+    let result = a.min(&b);
+    assert_eq!(result.get_data(), 2.);
+    assert_eq!(result.op().to_string(), "min");
+    result.backward();
+    assert_eq!((a.get_grad(), b.get_grad()), (1., 0.));
+
+    let (a, b) = (create_value(2.), create_value(2.));
+    a.max(&b).backward();
+    assert_eq!((a.get_grad(), b.get_grad()), (0.5, 0.5));
+
+    let a = create_value(2.);
+    a.min(&a).backward();
+    assert_eq!(a.get_grad(), 1.);
+}
+
+#[test]
+fn can_sign_value() {
+    let values = [-2., 0., 3.].map(create_value);
+
+    let result = values.iter().map(|v| v.sign()).collect::<Vec<_>>();
+    assert_eq!(result.iter().map(|v| v.get_data()).collect::<Vec<_>>(), vec![-1., 0., 1.]);
+    assert_eq!(result[0].op().to_string(), "sign");
+    assert_eq!(result[0].children.len(), 1);
+
+    let x = create_value(3.);
+    let y = x.sign() * &x;
+    y.backward();
+    assert_eq!(x.get_grad(), 1.);
+}
+
+#[test]
+fn can_erf_value() {
+    let result = create_value(0.).erf();
+    assert_eq!(result.get_data(), 0.);
+    assert_eq!(result.op().to_string(), "erf");
+    assert_eq!(result.children.len(), 1);
+
+    for (x, expected) in [(0.5, 0.5204998778130465), (1., 0.8427007929497149), (-2., -0.9953222650189527)]
+        .into_iter()
+        .chain([(3.5, 0.9999992569016276), (10., 1.)])
+    {
+        assert!((create_value(x).erf().get_data() - expected).abs() < 1E-14);
+    }
+
+    assert_numerical_grad(0.3, |x| x.erf());
+    assert_numerical_grad(-1.7, |x| x.erf());
+}
+
+#[test]
+fn can_powi_value() {
+    let result = create_value(3.).powi(2);
+    assert_eq!(result.get_data(), 9.);
+    assert_eq!(result.op().to_string(), "powi");
+    assert_eq!(result.children.len(), 1);
+
+    let x = create_value(-2.);
+    x.powi(3).backward();
+    assert_eq!(x.get_grad(), 12.);
+
+    assert_numerical_grad(1.5, |x| x.powi(-2));
+}
+
+#[test]
+fn can_pow_value_by_value() {
+    let (base, exponent) = (create_value(2.), create_value(3.));
+
+    let result = base.pow_value(&exponent);
+    assert_eq!(result.get_data(), 8.);
+    assert_eq!(result.op().to_string(), "pow_value");
+    assert_eq!(result.children.len(), 2);
+
+    result.backward();
+    assert_eq!(base.get_grad(), 12.);
+    assert_eq!(exponent.get_grad(), 8. * 2_f64.ln());
+
+    assert_numerical_grad(1.5, |x| x.pow_value(x));
+    assert_numerical_grad(0.5, |x| create_value(3.).pow_value(x));
+}
+
+#[test]
+fn can_recip_value() {
+    let result = create_value(4.).recip();
+    assert_eq!(result.get_data(), 0.25);
+    assert_eq!(result.op().to_string(), "recip");
+    assert_eq!(result.children.len(), 1);
+
+    assert_numerical_grad(-1.5, |x| x.recip());
+}
+
+#[test]
+fn can_divide_values_with_single_node() {
+    let (lhs, rhs) = (create_value(3.), create_value(2.));
+
+    let result = &lhs / &rhs;
+    assert_eq!(*result.children, vec![lhs.clone(), rhs.clone()]);
+    assert!(result.children.iter().all(|child| child.children.is_empty()));
+
+    result.backward();
+    assert_eq!(lhs.get_grad(), 0.5);
+    assert_eq!(rhs.get_grad(), -0.75);
+
+    assert_numerical_grad(1.5, |x| x / x);
+    assert_numerical_grad(1.5, |x| 2. / x);
+    assert_numerical_grad(1.5, |x| x - 3. * x);
+}
+
+#[test]
+fn can_apply_expm1_and_ln_1p() {
+    let result = create_value(1E-10).expm1();
+    assert_eq!(result.get_data(), 1E-10_f64.exp_m1());
+    assert_eq!(result.op().to_string(), "expm1");
+    assert_eq!(result.children.len(), 1);
+
+    let result = create_value(1E-10).ln_1p();
+    assert_eq!(result.get_data(), 1E-10_f64.ln_1p());
+    assert_eq!(result.op().to_string(), "ln_1p");
+    assert_eq!(result.children.len(), 1);
+    assert!(create_value(-1.).ln_1p().get_data().is_nan());
+
+    assert_numerical_grad(0.01, |x| x.expm1());
+    assert_numerical_grad(0.01, |x| x.ln_1p());
+}
+
+#[test]
+fn can_logsumexp_values() {
+    let values = [1., 2., 3.].map(create_value);
+
+    let result = Value::logsumexp(&values);
+    let expected = (1_f64.exp() + 2_f64.exp() + 3_f64.exp()).ln();
+    assert!((result.get_data() - expected).abs() < 1E-12);
+    assert_eq!(result.op().to_string(), "logsumexp");
+    assert_eq!(result.children.len(), 3);
+
+    result.backward();
+    let grads = values.iter().map(|v| v.get_grad()).collect::<Vec<_>>();
+    assert!((grads.iter().sum::<f64>() - 1.).abs() < 1E-12);
+    assert!((grads[2] - 3_f64.exp() / expected.exp()).abs() < 1E-12);
+
+    let result = Value::logsumexp(&[create_value(1000.), create_value(1000.)]);
+    assert_eq!(result.get_data(), 1000. + 2_f64.ln());
+
+    assert_eq!(Value::logsumexp(&[]).get_data(), f64::NEG_INFINITY);
+    assert_numerical_grad(0.5, |x| Value::logsumexp(&[x.clone(), x * 2., create_value(1.)]));
+}
+
+#[test]
+fn can_use_compound_assignment() {
+    let x = create_value(2.);
+
+    let mut result = create_value(1.);
+    result += &x;
+    result *= x.clone();
+    result -= 1.;
+    result /= &x;
+    assert_eq!(result.get_data(), 2.5);
+    assert_eq!(result.op().to_string(), "div");
+
+    assert_numerical_grad(2., |x| {
+        let mut result = create_value(1.);
+        result += x;
+        result *= x;
+        result -= 1.;
+        result /= x;
+        result
+    });
+}
+
+#[test]
+fn can_sum_and_multiply_value_references() {
+    let values = [2., 3., 4.].map(create_value);
+
+    let sum = values.iter().sum::<Value>();
+    assert_eq!(sum.get_data(), 9.);
+
+    let product = values.iter().product::<Value>();
+    assert_eq!(product.get_data(), 24.);
+    assert_eq!(values.iter().cloned().product::<Value>().get_data(), 24.);
+
+    product.backward();
+    assert_eq!(values.map(|v| v.get_grad()), [12., 8., 6.]);
+}
+
+#[test]
+fn can_convert_into_value() {
+    let value: Value = 2.5_f64.into();
+    assert_eq!(value.get_data(), 2.5);
+
+    let value = Value::from(1.5_f32);
+    assert_eq!(value.get_data(), 1.5);
+
+    assert_eq!(super::super::new_value(3).get_data(), 3.);
+    assert_eq!(super::super::new_value(0.5_f32).get_data(), 0.5);
+}
+
+#[test]
+fn can_compare_values_by_data() {
+    let (lhs, rhs) = (create_value(1.), create_value(2.));
+
+    assert!(lhs < rhs);
+    assert!(rhs >= lhs);
+    assert!(lhs <= lhs);
+    assert_eq!(lhs.partial_cmp(&lhs.clone()), Some(Ordering::Equal));
+
+    // different nodes with the same data are ordered as equal, but not equal
+    let same = create_value(1.);
+    assert_eq!(same.partial_cmp(&lhs), Some(Ordering::Equal));
+    assert!(same <= lhs);
+    assert!(same >= lhs);
+    assert_ne!(same, lhs);
+    assert_eq!(create_value(f64::NAN).partial_cmp(&lhs), None);
+    assert_eq!(create_value(1.).cmp_data(&lhs), Ordering::Equal);
+    assert_eq!(rhs.cmp_data(&lhs), Ordering::Greater);
+
+    let values = [3., 7., 5., 7.].map(create_value);
+    let max = values.iter().max_by(|a, b| a.cmp_data(b)).unwrap();
+    assert_eq!(max, &values[3]);
+}
+
+#[test]
+fn can_round_value() {
+    let x = create_value(2.5);
+
+    let (floor, ceil, round) = (x.floor(), x.ceil(), x.round());
+    assert_eq!((floor.get_data(), ceil.get_data(), round.get_data()), (2., 3., 3.));
+    assert_eq!(
+        (floor.op().to_string().as_str(), ceil.op().to_string().as_str(), round.op().to_string().as_str()),
+        ("floor", "ceil", "round")
+    );
+
+    (floor + ceil + round + &x).backward();
+    assert_eq!(x.get_grad(), 1.);
+}
+
+#[test]
+fn can_atan2_values() {
+    let (y, x) = (create_value(1.), create_value(-1.));
+
+    let result = y.atan2(&x);
+    assert_eq!(result.get_data(), 3. * std::f64::consts::FRAC_PI_4);
+    assert_eq!(result.op().to_string(), "atan2");
+    assert_eq!(result.children.len(), 2);
+
+    result.backward();
+    assert_eq!((y.get_grad(), x.get_grad()), (-0.5, -0.5));
+
+    assert_numerical_grad(0.7, |y| y.atan2(&create_value(-2.)));
+    assert_numerical_grad(0.7, |x| create_value(-2.).atan2(x));
+}
+
+#[test]
+fn can_use_straight_through_estimators() {
+    let x = create_value(-0.4);
+
+    let sign = x.sign_ste();
+    let round = x.round_ste();
+    assert_eq!((sign.get_data(), round.get_data()), (-1., -0.));
+    assert_eq!((sign.op().to_string().as_str(), round.op().to_string().as_str()), ("sign_ste", "round_ste"));
+
+    (sign * 2. + round * 3.).backward();
+    assert_eq!(x.get_grad(), 5.);
+}
+
+#[test]
+fn can_safe_ln_value() {
+    let result = create_value(0.).safe_ln(1E-7);
+    assert_eq!(result.get_data(), 1E-7_f64.ln());
+    assert_eq!(result.op().to_string(), "safe_ln");
+    assert_eq!(result.children.len(), 1);
+
+    let x = create_value(0.);
+    x.safe_ln(1E-3).backward();
+    assert_eq!(x.get_grad(), 1E3);
+
+    assert_numerical_grad(0.5, |x| x.safe_ln(1E-7));
+}
+
+#[test]
+fn can_softsign_value() {
+    let result = create_value(3.).softsign();
+    assert_eq!(result.get_data(), 0.75);
+    assert_eq!(result.op().to_string(), "softsign");
+    assert_eq!(result.children.len(), 1);
+
+    assert_numerical_grad(3., |x| x.softsign());
+    assert_numerical_grad(-0.5, |x| x.softsign());
+}
+
+#[test]
+fn can_apply_hard_activations() {
+    let data = [-4., -0.5, 0., 2., 4.];
+
+    let result = data.map(|x| create_value(x).hard_tanh().get_data());
+    assert_eq!(result, [-1., -0.5, 0., 1., 1.]);
+    assert_eq!(create_value(0.).hard_tanh().op().to_string(), "hard_tanh");
+
+    let result = data.map(|x| create_value(x).hard_sigmoid().get_data());
+    assert_eq!(result, [0., 0.5 - 0.5 / 6., 0.5, 0.5 + 2. / 6., 1.]);
+    assert_eq!(create_value(0.).hard_sigmoid().op().to_string(), "hard_sigmoid");
+
+    let values = data.map(create_value);
+    values.iter().map(|x| x.hard_tanh() + x.hard_sigmoid()).sum::<Value>().backward();
+    assert_eq!(values.map(|v| v.get_grad()), [0., 1. + 1. / 6., 1. + 1. / 6., 1. / 6., 0.]);
+}
+
+#[test]
+fn can_calculate_gradient_of_deep_graph() {
+    let x = create_value(1.);
+
+    let mut result = create_value(0.);
+    for _ in 0..100_000 {
+        result += &x;
+    }
+    result.backward();
+
+    assert_eq!(result.get_data(), 100_000.);
+    assert_eq!(x.get_grad(), 100_000.);
+}
+
+#[test]
+fn can_detach_value() {
+    let x = create_value(3.);
+    let y = &x * &x;
+
+    let detached = y.detach();
+    assert_eq!(detached.get_data(), 9.);
+    assert!(detached.children.is_empty());
+    assert!(detached.backward_fn.is_none());
+    assert_ne!(detached, y);
+
+    let z = &detached * &x;
+    z.backward();
+    assert_eq!(x.get_grad(), 9.);
+    assert_eq!(y.get_grad(), 0.);
+}
+
+#[test]
+fn can_evaluate_without_recording_graph() {
+    let x = create_value(2.);
+
+    let result = no_grad(|| {
+        assert!(!is_grad_enabled());
+        (&x * &x + 1.).relu()
+    });
+    assert!(is_grad_enabled());
+
+    assert_eq!(result.get_data(), 5.);
+    assert_eq!(result.op().to_string(), "relu");
+    assert!(result.children.is_empty());
+    assert!(result.backward_fn.is_none());
+
+    result.backward();
+    assert_eq!(x.get_grad(), 0.);
+
+    let result = no_grad(|| no_grad(|| Value::logsumexp(std::slice::from_ref(&x))) + &x);
+    assert!(result.children.is_empty());
+    assert!(is_grad_enabled());
+}
+
+#[test]
+fn can_call_backward_multiple_times() {
+    let x = create_value(3.);
+    let y = &x * &x;
+    let z = &y + &y;
+
+    z.backward_retain();
+    assert_eq!(x.get_grad(), 12.);
+    assert_eq!(y.get_grad(), 2.);
+
+    z.backward_retain();
+    assert_eq!(x.get_grad(), 24.);
+    assert_eq!(y.get_grad(), 2.);
+
+    let w = y.relu();
+    w.backward();
+    assert_eq!(x.get_grad(), 30.);
+
+    z.zero_intermediate_grads();
+    assert_eq!(y.get_grad(), 0.);
+    assert_eq!(x.get_grad(), 30.);
+}
+
+#[test]
+fn can_call_backward_once() {
+    let x = create_value(3.);
+    let y = &x * &x;
+    let deep = (0..100_000).fold(y.clone(), |acc, _| acc + 1.);
+    assert_eq!(Shared::strong_count(&y.children), 2);
+
+    deep.backward_once();
+
+    assert_eq!(x.get_grad(), 6.);
+    assert_eq!(y.get_grad(), 1.);
+    assert_eq!(Shared::strong_count(&y.children), 1);
+    y.backward();
+    assert_eq!(x.get_grad(), 12.);
+}
+
+#[test]
+fn can_reevaluate_graph_with_forward() {
+    let (mut x, mut y) = (create_value(1.), create_value(2.));
+    let z = (&x * &y + x.exp()).relu() / &y + Value::logsumexp(&[x.clone(), y.clone()]) + &x * &x;
+    let expected = |x: f64, y: f64| (x * y + x.exp()).max(0.) / y + (x.exp() + y.exp()).ln() + x * x;
+
+    x.set_data(-3.);
+    y.set_data(0.5);
+    assert_eq!(z.get_data(), expected(1., 2.));
+
+    z.forward();
+    assert!((z.get_data() - expected(-3., 0.5)).abs() < 1E-12);
+
+    z.backward();
+    let eps = 1E-6;
+    let expected_grad = (expected(-3. + eps, 0.5) - expected(-3. - eps, 0.5)) / (2. * eps);
+    assert!((x.get_grad() - expected_grad).abs() < 1E-6);
+}
+
+#[test]
+fn can_build_gradient_graph_matching_backward() {
+    #[allow(clippy::type_complexity)]
+    let functions: Vec<(f64, Box<dyn Fn(&Value) -> Value>)> = vec![
+        (0.7, Box::new(|x| (x * x - x / 3. + 2. / x).relu() * x.tanh() + x.sigmoid() * x.exp())),
+        (0.7, Box::new(|x| x.ln() + x.log(3.) + x.log2() + x.log10() + x.sqrt() + (-x).abs())),
+        (0.7, Box::new(|x| x.sin() * x.cos() + x.tan() + x.atan() + x.sinh() / x.cosh() + x.erf())),
+        (-0.7, Box::new(|x| x.elu(0.5) + x.silu() + x.softplus() + x.clamp(-1., 1.) + x.powi(3) + x.pow(2.))),
+        (0.7, Box::new(|x| x.max(&(x * 2.)) + x.min(&x.exp()) + x.pow_value(x) + x.atan2(&(x + 1.)))),
+        (0.7, Box::new(|x| x.recip() + x.expm1() + x.ln_1p() + Value::logsumexp(&[x.clone(), x * x]))),
+        (0.7, Box::new(|x| x.sign() + x.floor() + x.ceil() + x.round() + x.sign_ste() + x.round_ste())),
+        (0.7, Box::new(|x| x.safe_ln(1E-3) + x.softsign() + x.hard_tanh() + x.hard_sigmoid())),
+        (0.7, Box::new(|x| Value::fma(x, &x.exp(), x) + Value::fma(x, x, &(x * 2.)))),
+    ];
+
+    functions.iter().for_each(|(x, func)| {
+        let x = create_value(*x);
+        let y = func(&x);
+
+        let gradient = y.grad_graph(std::slice::from_ref(&x)).remove(0);
+        assert_eq!(x.get_grad(), 0.);
+
+        y.backward();
+        assert!((gradient.get_data() - x.get_grad()).abs() < 1E-9, "{} != {}", gradient.get_data(), x.get_grad());
+    });
+}
+
+#[test]
+fn can_calculate_second_order_derivatives() {
+    let (x, y) = (create_value(2.), create_value(3.));
+    let z = x.powi(3) * &y + x.sin();
+
+    let gradients = z.grad_graph(&[x.clone(), y.clone(), create_value(1.)]);
+    assert_eq!(gradients[0].get_data(), 3. * 4. * 3. + 2_f64.cos());
+    assert_eq!(gradients[1].get_data(), 8.);
+    assert_eq!(gradients[2].get_data(), 0.);
+
+    gradients[0].backward();
+    assert!((x.get_grad() - (6. * 2. * 3. - 2_f64.sin())).abs() < 1E-12);
+    assert_eq!(y.get_grad(), 12.);
+}
+
+#[test]
+fn can_checkpoint_computation() {
+    let func = |x: &Value, y: &Value| (0..1000).fold(x * y, |acc, _| (acc * 0.9 + y).tanh());
+
+    let (x, y) = (create_value(0.5), create_value(-0.3));
+    let expected = func(&x, &y);
+    expected.backward();
+    let (expected_x_grad, expected_y_grad) = (x.get_grad(), y.get_grad());
+
+    let (x, y) = (create_value(0.5), create_value(-0.3));
+    let result = checkpoint(&[x.clone(), y.clone()], move |inputs| func(&inputs[0], &inputs[1])) * 2.;
+    result.backward();
+
+    assert_eq!(result.topological_order().len(), 5);
+    assert_eq!(result.get_data(), expected.get_data() * 2.);
+    assert!((x.get_grad() - expected_x_grad * 2.).abs() < 1E-12);
+    assert!((y.get_grad() - expected_y_grad * 2.).abs() < 1E-12);
+
+    let gradients = result.grad_graph(&[x.clone(), y.clone()]);
+    assert!((gradients[0].get_data() - expected_x_grad * 2.).abs() < 1E-12);
+    assert!((gradients[1].get_data() - expected_y_grad * 2.).abs() < 1E-12);
+}
+
+#[test]
+fn can_checkpoint_computation_with_captured_values() {
+    let w = create_value(3.);
+    let x = create_value(2.);
+
+    let captured = w.clone();
+    let result = checkpoint(std::slice::from_ref(&x), move |inputs| (&inputs[0] * &captured).exp()) * 0.5;
+    result.backward();
+
+    assert!((x.get_grad() - 0.5 * 3. * 6_f64.exp()).abs() < 1E-9);
+    assert!((w.get_grad() - 0.5 * 2. * 6_f64.exp()).abs() < 1E-9);
+}
+
+#[test]
+fn can_backward_checkpoint_when_recording_is_disabled() {
+    let (x, w) = (create_value(2.), create_value(3.));
+    let captured = w.clone();
+    let result = checkpoint(std::slice::from_ref(&x), move |inputs| (&inputs[0] * &captured).powi(2));
+    let expected_x_grad = 2. * 6. * 3.;
+
+    no_grad(|| result.backward());
+    assert_eq!((x.get_grad(), w.get_grad()), (expected_x_grad, 2. * 6. * 2.));
+
+    let tape = Tape::new();
+    assert!(!tape.is_recording());
+    let gradients = result.grad_graph(std::slice::from_ref(&x));
+    assert_eq!(gradients[0].get_data(), expected_x_grad);
+    assert!(!gradients[0].children.is_empty());
+    assert_eq!(jvp(std::slice::from_ref(&x), &[1.], std::slice::from_ref(&result)), vec![expected_x_grad]);
+    drop(tape);
+
+    assert!(is_grad_enabled());
+}
+
+#[test]
+fn can_calculate_vector_jacobian_product() {
+    let (x, y) = (create_value(2.), create_value(3.));
+    let outputs = [&x * &y, x.sin() + &y];
+
+    vjp(&outputs, &[2., -1.]);
+
+    assert!((x.get_grad() - (2. * 3. - 2_f64.cos())).abs() < 1E-12);
+    assert_eq!(y.get_grad(), 2. * 2. - 1.);
+}
+
+#[test]
+fn can_calculate_jacobian_vector_product() {
+    let (x, y) = (create_value(2.), create_value(3.));
+    let hidden = &x * &y;
+    let outputs = [hidden.exp(), x.sin() + &y, create_value(1.)];
+
+    let result = jvp(&[x.clone(), y.clone()], &[1., 0.5], &outputs);
+
+    assert!((result[0] - 6_f64.exp() * (3. + 0.5 * 2.)).abs() < 1E-9);
+    assert!((result[1] - (2_f64.cos() + 0.5)).abs() < 1E-12);
+    assert_eq!(result[2], 0.);
+    assert_eq!(x.get_grad(), 0.);
+
+    let result = jvp(std::slice::from_ref(&hidden), &[2.], &outputs[..1]);
+    assert!((result[0] - 6_f64.exp() * 2.).abs() < 1E-9);
+}
+
+#[test]
+fn can_modify_gradients_with_hooks() {
+    let x = create_value(2.);
+    let hidden = &x * 10.;
+    let result = hidden.powi(2);
+
+    let observed = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let observed_clone = observed.clone();
+    hidden.register_hook(|grad| grad.clamp(-1., 1.));
+    x.register_hook(move |grad| {
+        observed_clone.lock().unwrap().push(grad);
+        grad
+    });
+
+    result.backward();
+    result.backward();
+
+    assert_eq!(*observed.lock().unwrap(), vec![10., 10.]);
+    assert_eq!(x.get_grad(), 20.);
+
+    hidden.clear_hooks();
+    x.clear_hooks();
+    x.register_hook(|grad| grad * 0.5);
+    result.backward();
+    assert_eq!(x.get_grad(), 20. + 0.5 * 400.);
+}
+
+#[test]
+fn can_label_value() {
+    let x = create_value(2.).with_label("x");
+    let mut y = &x * 3.;
+    assert_eq!(y.get_label(), None);
+    assert_eq!(y.to_string(), "Value[data=6, grad=0]");
+
+    y.set_label("y");
+    y.backward();
+
+    assert_eq!(x.get_label().as_deref(), Some("x"));
+    assert_eq!(format!("{:?}", y.children[0]), "Value[label=x, data=2, grad=3]");
+    assert_eq!(y.to_string(), "Value[label=y, data=6, grad=1]");
+}
+
+#[test]
+fn can_traverse_graph() {
+    let (x, y) = (create_value(2.), create_value(3.));
+    let hidden = &x * &y;
+    let result = (&hidden + &x).tanh();
+
+    let topo = result.iter_topo().collect::<Vec<_>>();
+    assert_eq!(topo.len(), 5);
+    assert_eq!(topo.last(), Some(&&result));
+    assert!(topo.iter().position(|v| *v == &x) < topo.iter().position(|v| *v == &hidden));
+
+    let leaves = result.iter_leaves().collect::<Vec<_>>();
+    assert_eq!(leaves.len(), 2);
+    assert!(leaves.contains(&&x) && leaves.contains(&&y));
+
+    assert_eq!(hidden.iter_children().collect::<Vec<_>>(), vec![&x, &y]);
+    assert_eq!(x.iter_children().count(), 0);
+}
+
+#[test]
+fn can_reuse_cached_topological_order() {
+    let mut x = create_value(2.);
+    let result = (&x * &x).tanh() + &x;
+    assert!(result.topo_cache.get().is_none());
+
+    result.backward();
+    let cached = result.topo_cache.get().cloned().unwrap();
+    assert_eq!(cached.len(), 3);
+
+    x.set_data(-1.);
+    result.forward();
+    x.zero_grad();
+    result.backward();
+
+    assert!(Shared::ptr_eq(&cached, result.topo_cache.get().unwrap()));
+    assert_eq!(result.get_data(), 1_f64.tanh() - 1.);
+    assert!((x.get_grad() - (1. - 1_f64.tanh().powi(2)) * -2. - 1.).abs() < 1E-12);
+}
+
+#[test]
+fn can_optimize_graph() {
+    let (x, y) = (create_value(2.), create_value(3.));
+    let not_foldable = (create_value(1.) * 0. + 2.).exp();
+    let result = (&x * &y).tanh() + (&x * &y).tanh() + &x * (Value::constant(3.) * 2.).ln() + x.pow(2.) * 4.;
+    assert_eq!(*(&x * 2.).children[1].op(), Op::Constant);
+
+    let optimized = result.optimize();
+
+    assert_eq!(optimized.get_data(), result.get_data());
+    assert!(optimized.graph_stats().nodes < result.graph_stats().nodes);
+    assert_eq!(optimized.graph_stats().ops.get("tanh"), Some(&1));
+    assert_eq!(optimized.graph_stats().ops.get("ln"), None);
+    assert_eq!(not_foldable.optimize().graph_stats().nodes, not_foldable.graph_stats().nodes);
+
+    optimized.backward();
+    let (x_grad, y_grad) = (x.get_grad(), y.get_grad());
+    let (mut x, mut y) = (x, y);
+    x.zero_grad();
+    y.zero_grad();
+    result.backward();
+    assert!((x.get_grad() - x_grad).abs() < 1E-12);
+    assert!((y.get_grad() - y_grad).abs() < 1E-12);
+}
+
+#[test]
+fn can_fma_values() {
+    let (a, b, c) = (create_value(2.), create_value(3.), create_value(4.));
+
+    let result = Value::fma(&a, &b, &c);
+    result.backward();
+
+    assert_eq!(result.get_data(), 10.);
+    assert_eq!(result.op().to_string(), "fma");
+    assert_eq!(result.children.len(), 3);
+    assert_eq!((a.get_grad(), b.get_grad(), c.get_grad()), (3., 2., 1.));
+
+    assert_numerical_grad(1.5, |x| Value::fma(x, x, x));
+}
+
+#[test]
+fn can_backward_to_given_nodes() {
+    let (x, frozen, w) = (create_value(2.), create_value(3.), create_value(-1.));
+    let hidden = (&x * &frozen).tanh();
+    let result = &hidden * &w + frozen.exp();
+
+    result.backward_to(&[hidden.clone(), w.clone()]);
+
+    assert_eq!(x.get_grad(), 0.);
+    assert_eq!(frozen.get_grad(), 0.);
+    assert_eq!(hidden.get_grad(), -1.);
+    assert_eq!(w.get_grad(), hidden.get_data());
+
+    result.backward_to(std::slice::from_ref(&x));
+    assert!((x.get_grad() - -3. * (1. - hidden.get_data().powi(2))).abs() < 1E-12);
+    assert_eq!(frozen.get_grad(), 0.);
+    assert_eq!(w.get_grad(), hidden.get_data());
+}
+
+#[test]
+fn can_choose_grad_mode() {
+    let x = create_value(2.);
+    let result = &x * &x * 3.;
+
+    result.backward_with_mode(GradMode::Overwrite);
+    result.backward_with_mode(GradMode::Overwrite);
+    assert_eq!(x.get_grad(), 12.);
+
+    result.backward_with_mode(GradMode::Accumulate);
+    assert_eq!(x.get_grad(), 24.);
+
+    x.register_hook(|grad| grad / 2.);
+    result.backward_with_mode(GradMode::Overwrite);
+    assert_eq!(x.get_grad(), 6.);
+}
+
+#[test]
+fn can_backward_with_seed_gradient() {
+    let x = create_value(2.);
+    let result = x.powi(3);
+
+    result.backward_with(0.5);
+
+    assert_eq!(result.get_grad(), 0.5);
+    assert_eq!(x.get_grad(), 0.5 * 12.);
+    assert_eq!(result.graph_stats().nodes, 2);
+}
+
+#[test]
+fn can_estimate_memory_footprint() {
+    let x = create_value(2.);
+    let leaf_size = x.memory_footprint();
+    assert!(leaf_size >= std::mem::size_of::<Value>() + std::mem::size_of::<Float>() * 2);
+
+    let small = (&x * &x).tanh();
+    let large = (0..100).fold(x.clone(), |acc, _| (acc * &x).tanh());
+
+    assert!(small.memory_footprint() > leaf_size);
+    assert!(large.memory_footprint() > 50 * small.memory_footprint());
+    assert_eq!((&x + &x).memory_footprint(), (&x + &x).memory_footprint());
+}
+
+#[test]
+fn can_deep_clone_graph() {
+    let x = create_value(2.).with_label("x");
+    let result = (&x * &x).tanh() + &x * 3.;
+    result.backward();
+
+    let copy = result.deep_clone();
+    let copy_x = copy.iter_leaves().find(|leaf| leaf.get_label().as_deref() == Some("x")).cloned().unwrap();
+
+    assert_eq!(copy.get_data(), result.get_data());
+    assert_eq!(copy_x.get_grad(), x.get_grad());
+    assert_eq!(copy.graph_stats(), result.graph_stats());
+    assert_ne!(copy_x, x);
+
+    let x_grad = x.get_grad();
+    copy.backward();
+    assert!((copy_x.get_grad() - 2. * x_grad).abs() < 1E-12);
+    assert_eq!(x.get_grad(), x_grad);
+}
+
+#[test]
+fn can_deep_clone_graph_with_checkpoint_and_custom_nodes() {
+    let x = create_value(2.).with_label("x");
+    let checkpointed = checkpoint(std::slice::from_ref(&x), |inputs| (&inputs[0] * 3.).tanh());
+    let result = checkpointed.custom_unary(|v| v * v, |v, _| 2. * v, "square") + &x;
+
+    let copy = result.deep_clone();
+    let copy_x = copy.iter_leaves().find(|leaf| leaf.get_label().as_deref() == Some("x")).cloned().unwrap();
+    copy.backward();
+
+    assert_eq!(copy.get_data(), result.get_data());
+    assert!(copy.topological_order().iter().all(|v| !result.topological_order().contains(v)));
+    assert_eq!(x.get_grad(), 0.);
+    let expected = 2. * 6_f64.tanh() * 3. * (1. - 6_f64.tanh().powi(2)) + 1.;
+    assert!((copy_x.get_grad() - expected).abs() < 1E-12);
+}
+
+#[test]
+fn can_compare_graphs_structurally() {
+    let build = |x: &Value, y: &Value| (x * y).tanh() + x.pow(2.);
+    let (x1, y1, x2, y2) = (create_value(2.), create_value(3.), create_value(2.), create_value(3.));
+
+    let lhs = build(&x1, &y1);
+    let rhs = build(&x2, &y2);
+    assert!(lhs.structurally_eq(&rhs));
+    assert!(lhs.structurally_eq(&lhs.deep_clone()));
+    assert_ne!(lhs, rhs);
+
+    assert!(!lhs.structurally_eq(&build(&y2, &x2)));
+    assert!(!lhs.structurally_eq(&build(&x2, &create_value(4.))));
+    assert!(!lhs.structurally_eq(&((&x2 * &y2).tanh() + x2.pow(3.))));
+    assert!(!(&x1 + &x1).structurally_eq(&(&x1 + &create_value(2.))));
+}
+
+#[test]
+fn can_use_value_identity() {
+    let (x, y) = (create_value(2.), create_value(2.));
+    let z = &x + &y;
+
+    assert_eq!(x.id(), x.clone().id());
+    assert_ne!(x.id(), y.id());
+    assert_eq!(z.children[0].id(), x.id());
+
+    let mut map = IdMap::new();
+    assert_eq!(map.insert(&x, 1), None);
+    assert_eq!(map.insert(&y, 2), None);
+    assert_eq!(map.insert(&x.clone(), 3), Some(1));
+    *map.get_mut(&y).unwrap() += 1;
+
+    assert_eq!((map.len(), map.get(&x), map.get(&y)), (2, Some(&3), Some(&3)));
+    assert!(map.contains(&z.children[1]) && !map.contains(&z));
+    assert_eq!(map.remove(&x), Some(3));
+    assert_eq!(map.iter().collect::<Vec<_>>(), vec![(y.id(), &3)]);
+}
+
+#[test]
+fn can_register_custom_operations() {
+    let (mut x, y) = (create_value(2.), create_value(3.));
+
+    let cube = x.custom_unary(|x| x * x * x, |x, _| 3. * x * x, "cube");
+    let hypot = cube.custom_binary(&y, |a, b| a.hypot(b), |a, b| (a / a.hypot(b), b / a.hypot(b)), "hypot");
+
+    assert_eq!(cube.op().to_string(), "cube");
+    assert_eq!(cube.get_data(), 8.);
+    assert!((hypot.get_data() - 73_f64.sqrt()).abs() < 1E-12);
+
+    hypot.backward();
+    assert!((x.get_grad() - 12. * 8. / 73_f64.sqrt()).abs() < 1E-12);
+    assert!((y.get_grad() - 3. / 73_f64.sqrt()).abs() < 1E-12);
+
+    let derivatives = cube.grad_graph(&[x.clone()]);
+    assert_eq!(derivatives[0].get_data(), 12.);
+
+    let square = x.custom_binary(&x, |a, b| a * b, |a, b| (b, a), "square");
+    x.set_data(4.);
+    square.forward();
+    assert_eq!(square.get_data(), 16.);
+    x.zero_grad();
+    square.backward();
+    assert_eq!(x.get_grad(), 8.);
+}
+
+#[test]
+fn can_simplify_graph() {
+    let (x, y) = (create_value(2.), create_value(3.));
+
+    assert_eq!(((&x * 1.) + 0.).simplify(), x);
+    assert_eq!((&x - 0.).pow(1.).powi(1).simplify(), x);
+    assert_eq!((-(-&x) / 1.).simplify(), x);
+    assert_eq!((Value::constant(1.) * &x).simplify(), x);
+
+    let result = (-(&x * 2.) * 3.).simplify();
+    assert_eq!((result.op().clone(), result.get_data(), result.children.len()), (Op::Mul, -12., 2));
+    assert_eq!((result.children[0].clone(), result.children[1].get_data()), (x.clone(), -6.));
+
+    let result = ((&x + 1.) + 2.) * &y - (Value::constant(2.) * 3.);
+    let simplified = result.simplify();
+    assert_eq!(simplified.get_data(), result.get_data());
+    assert_eq!(simplified.graph_stats().nodes, 7);
+    assert_eq!(result.graph_stats().nodes, 11);
+
+    simplified.backward();
+    assert_eq!((x.get_grad(), y.get_grad()), (3., 5.));
+
+    let leaf = create_value(1.);
+    assert_eq!(leaf.simplify(), leaf);
+}
+
+#[test]
+fn can_record_operations_with_tape() {
+    let x = create_value(2.);
+
+    {
+        let tape = Tape::new();
+        assert!(!tape.is_recording());
+
+        let untracked = &x * 3.;
+        tape.start();
+        let tracked = &x * 4.;
+        tape.stop();
+        let recorded = tape.record(|| &x * 5.);
+
+        assert!(untracked.children.is_empty() && !tracked.children.is_empty() && !recorded.children.is_empty());
+        assert_eq!(untracked.get_data(), 6.);
+        assert!(!tape.is_recording());
+
+        (&untracked + &tracked + &recorded).backward();
+        assert_eq!(x.get_grad(), 0.);
+
+        tape.record(|| untracked + tracked + recorded).backward();
+
+        // nested record keeps recording started before
+        tape.start();
+        let nested = tape.record(|| &x * 6.);
+        assert!(tape.is_recording());
+        assert!(!(&nested * 2.).children.is_empty());
+        tape.stop();
+    }
+
+    assert_eq!(x.get_grad(), 9.);
+    assert!(is_grad_enabled());
+}